@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS rustack.products;
+DROP TABLE IF EXISTS rustack.users;
+DROP SCHEMA IF EXISTS rustack;
@@ -0,0 +1,31 @@
+-- Application schema
+CREATE SCHEMA IF NOT EXISTS rustack;
+
+-- Create users table
+CREATE TABLE rustack.users (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    username VARCHAR(100) UNIQUE NOT NULL,
+    email VARCHAR(255) UNIQUE NOT NULL,
+    password_hash VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create products table
+CREATE TABLE rustack.products (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    price DECIMAL(10, 2) NOT NULL CHECK (price >= 0),
+    stock INTEGER NOT NULL DEFAULT 0 CHECK (stock >= 0),
+    created_by UUID REFERENCES rustack.users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes for better performance
+CREATE INDEX idx_users_username ON rustack.users(username);
+CREATE INDEX idx_users_email ON rustack.users(email);
+CREATE INDEX idx_products_name ON rustack.products(name);
+CREATE INDEX idx_products_price ON rustack.products(price);
+CREATE INDEX idx_products_created_by ON rustack.products(created_by);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS rustack.user_roles;
+DROP TABLE IF EXISTS rustack.role_permissions;
+DROP TABLE IF EXISTS rustack.permissions;
+DROP TABLE IF EXISTS rustack.roles;
@@ -0,0 +1,56 @@
+-- Create roles table
+CREATE TABLE rustack.roles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(50) UNIQUE NOT NULL,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create permissions table
+CREATE TABLE rustack.permissions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) UNIQUE NOT NULL,
+    description TEXT
+);
+
+-- Create role/permission mapping table
+CREATE TABLE rustack.role_permissions (
+    role_id UUID NOT NULL REFERENCES rustack.roles(id) ON DELETE CASCADE,
+    permission_id UUID NOT NULL REFERENCES rustack.permissions(id) ON DELETE CASCADE,
+    PRIMARY KEY (role_id, permission_id)
+);
+
+-- Create user/role mapping table
+CREATE TABLE rustack.user_roles (
+    user_id UUID NOT NULL REFERENCES rustack.users(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES rustack.roles(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, role_id)
+);
+
+CREATE INDEX idx_user_roles_role_id ON rustack.user_roles(role_id);
+
+-- Insert roles
+INSERT INTO rustack.roles (name, description) VALUES
+('admin', 'Full access to all resources'),
+('user', 'Read users, manage products');
+
+-- Insert permissions
+INSERT INTO rustack.permissions (name, description) VALUES
+('users:read', 'List and view users'),
+('users:write', 'Create and update users'),
+('users:delete', 'Delete users'),
+('products:read', 'Search and view products'),
+('products:write', 'Create and update products'),
+('products:delete', 'Delete products'),
+('roles:read', 'View roles and role assignments'),
+('roles:write', 'Assign and revoke roles');
+
+-- Grant permissions to roles
+INSERT INTO rustack.role_permissions (role_id, permission_id)
+SELECT r.id, p.id FROM rustack.roles r CROSS JOIN rustack.permissions p WHERE r.name = 'admin';
+
+INSERT INTO rustack.role_permissions (role_id, permission_id)
+SELECT r.id, p.id FROM rustack.roles r JOIN rustack.permissions p
+    ON p.name IN ('users:read', 'products:read', 'products:write', 'products:delete')
+WHERE r.name = 'user';
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS rustack.user_recovery_codes;
+DROP TABLE IF EXISTS rustack.user_totp;
@@ -0,0 +1,19 @@
+-- TOTP (RFC 6238) secrets; a row with enabled_at NULL is an unconfirmed enrollment
+CREATE TABLE rustack.user_totp (
+    user_id UUID PRIMARY KEY REFERENCES rustack.users(id) ON DELETE CASCADE,
+    secret BYTEA NOT NULL,
+    enabled_at TIMESTAMPTZ,
+    -- Highest time step accepted so far, so a code cannot be replayed
+    last_used_step BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Single-use recovery codes, stored as SHA-256 hashes
+CREATE TABLE rustack.user_recovery_codes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES rustack.users(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, code_hash)
+);
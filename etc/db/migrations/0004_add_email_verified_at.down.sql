@@ -0,0 +1 @@
+ALTER TABLE rustack.users DROP COLUMN IF EXISTS email_verified_at;
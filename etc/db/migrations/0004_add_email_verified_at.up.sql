@@ -0,0 +1,2 @@
+-- Set when the user follows a verification link sent to their current email
+ALTER TABLE rustack.users ADD COLUMN email_verified_at TIMESTAMPTZ;
@@ -0,0 +1 @@
+DELETE FROM rustack.permissions WHERE name = 'sessions:read';
@@ -0,0 +1,9 @@
+-- Other users' session IPs, user agents and activity are for administrators only
+INSERT INTO rustack.permissions (name, description) VALUES
+('sessions:read', 'View other users'' active sessions')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO rustack.role_permissions (role_id, permission_id)
+SELECT r.id, p.id FROM rustack.roles r JOIN rustack.permissions p ON p.name = 'sessions:read'
+WHERE r.name = 'admin'
+ON CONFLICT DO NOTHING;
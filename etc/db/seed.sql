@@ -0,0 +1,29 @@
+-- Sample data for local development.
+-- Apply after the schema migrations: `rust-ack migrate up && psql $DATABASE_URL -f etc/db/seed.sql`
+
+-- Insert sample users (password is "password123" for all)
+INSERT INTO rustack.users (id, username, email, password_hash) VALUES
+('550e8400-e29b-41d4-a716-446655440000', 'admin', 'admin@example.com', '$2b$12$LQv3c1yqBWVHxkd0LHAkCOYz6TtxMQJqhN8/LewY5GyYPkJNXzTie'),
+('550e8400-e29b-41d4-a716-446655440001', 'user1', 'user1@example.com', '$2b$12$LQv3c1yqBWVHxkd0LHAkCOYz6TtxMQJqhN8/LewY5GyYPkJNXzTie'),
+('550e8400-e29b-41d4-a716-446655440002', 'user2', 'user2@example.com', '$2b$12$LQv3c1yqBWVHxkd0LHAkCOYz6TtxMQJqhN8/LewY5GyYPkJNXzTie')
+ON CONFLICT DO NOTHING;
+
+-- Insert sample products
+INSERT INTO rustack.products (id, name, description, price, stock, created_by) VALUES
+('6ba7b810-9dad-11d1-80b4-00c04fd43000', 'Laptop', 'High-performance laptop with 16GB RAM and 512GB SSD', 999.99, 50, '550e8400-e29b-41d4-a716-446655440000'),
+('6ba7b810-9dad-11d1-80b4-00c04fd43001', 'Mouse', 'Wireless ergonomic mouse', 29.99, 200, '550e8400-e29b-41d4-a716-446655440000'),
+('6ba7b810-9dad-11d1-80b4-00c04fd43002', 'Keyboard', 'Mechanical RGB keyboard', 79.99, 150, '550e8400-e29b-41d4-a716-446655440000'),
+('6ba7b810-9dad-11d1-80b4-00c04fd43003', 'Monitor', '27-inch 4K monitor', 399.99, 75, '550e8400-e29b-41d4-a716-446655440001'),
+('6ba7b810-9dad-11d1-80b4-00c04fd43004', 'Webcam', 'HD 1080p webcam', 59.99, 120, '550e8400-e29b-41d4-a716-446655440001'),
+('6ba7b810-9dad-11d1-80b4-00c04fd43005', 'Headphones', 'Noise-cancelling headphones', 149.99, 90, '550e8400-e29b-41d4-a716-446655440002')
+ON CONFLICT (id) DO NOTHING;
+
+-- Assign roles to sample users
+INSERT INTO rustack.user_roles (user_id, role_id)
+SELECT '550e8400-e29b-41d4-a716-446655440000', id FROM rustack.roles WHERE name = 'admin'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO rustack.user_roles (user_id, role_id)
+SELECT u.id, r.id FROM rustack.users u CROSS JOIN rustack.roles r
+WHERE r.name = 'user'
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,53 @@
+#!/usr/bin/env python3
+"""Minimal OTLP/HTTP collector stand-in for local tracing tests.
+
+Accepts protobuf trace exports on POST /v1/traces and logs each batch, so the
+exporter can be exercised without running a real OpenTelemetry Collector.
+
+Usage:
+    ./otlp_collector.py [port]        # default 4318
+    OTEL_EXPORTER_OTLP_ENDPOINT=http://localhost:4318 cargo run
+"""
+import sys
+from datetime import datetime
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+# W3C trace ids are 16 bytes; OTLP encodes them as protobuf field `trace_id = 1`
+# (tag 0x0a, length 0x10) inside each span, which is enough to count spans.
+TRACE_ID_TAG = b"\x0a\x10"
+
+
+class CollectorHandler(BaseHTTPRequestHandler):
+    def do_POST(self):
+        length = int(self.headers.get("Content-Length", 0))
+        body = self.rfile.read(length)
+
+        if self.path != "/v1/traces":
+            self.send_response(404)
+            self.end_headers()
+            return
+
+        trace_ids = set()
+        start = 0
+        while (index := body.find(TRACE_ID_TAG, start)) != -1:
+            trace_ids.add(body[index + 2 : index + 18].hex())
+            start = index + 18
+
+        timestamp = datetime.now().strftime("%H:%M:%S")
+        print(f"[{timestamp}] {len(body)} bytes, trace ids: {', '.join(sorted(trace_ids)) or '-'}")
+        sys.stdout.flush()
+
+        # An empty ExportTraceServiceResponse means every span was accepted
+        self.send_response(200)
+        self.send_header("Content-Type", "application/x-protobuf")
+        self.send_header("Content-Length", "0")
+        self.end_headers()
+
+    def log_message(self, format, *args):
+        pass
+
+
+if __name__ == "__main__":
+    port = int(sys.argv[1]) if len(sys.argv) > 1 else 4318
+    print(f"OTLP collector stand-in listening on http://localhost:{port}/v1/traces")
+    HTTPServer(("0.0.0.0", port), CollectorHandler).serve_forever()
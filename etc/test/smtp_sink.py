@@ -0,0 +1,95 @@
+#!/usr/bin/env python3
+"""Minimal SMTP server stand-in for local mail tests.
+
+Accepts every message and prints its envelope and body, so the SMTP mailer can
+be exercised without a real mail server. Optionally saves messages as .eml
+files, in the same layout as the file transport.
+
+Usage:
+    ./smtp_sink.py [port] [outbox_dir]   # default 1025, nothing saved
+    MAIL_TRANSPORT=smtp SMTP_PORT=1025 cargo run
+"""
+import asyncio
+import sys
+import uuid
+from datetime import datetime
+from pathlib import Path
+
+
+class Session:
+    def __init__(self, reader, writer, outbox):
+        self.reader = reader
+        self.writer = writer
+        self.outbox = outbox
+        self.sender = None
+        self.recipients = []
+
+    async def reply(self, line):
+        self.writer.write(f"{line}\r\n".encode())
+        await self.writer.drain()
+
+    async def read_data(self):
+        lines = []
+        while True:
+            line = (await self.reader.readline()).decode("utf-8", "replace").rstrip("\r\n")
+            if line == ".":
+                return "\r\n".join(lines) + "\r\n"
+            # Undo dot-stuffing
+            lines.append(line[1:] if line.startswith("..") else line)
+
+    def deliver(self, message):
+        timestamp = datetime.now().strftime("%H:%M:%S")
+        print(f"[{timestamp}] {self.sender} -> {', '.join(self.recipients)}")
+        print(message)
+        sys.stdout.flush()
+
+        if self.outbox:
+            self.outbox.mkdir(parents=True, exist_ok=True)
+            name = f"{datetime.now().strftime('%Y%m%dT%H%M%S.%f')}-{uuid.uuid4().hex}.eml"
+            (self.outbox / name).write_text(message)
+
+    async def run(self):
+        await self.reply("220 localhost SMTP sink ready")
+        while line := (await self.reader.readline()).decode().rstrip("\r\n"):
+            verb = line.split(" ", 1)[0].upper()
+            if verb in ("EHLO", "HELO"):
+                await self.reply("250-localhost")
+                await self.reply("250 AUTH PLAIN")
+            elif verb == "AUTH":
+                # Any credentials are accepted
+                await self.reply("235 Authentication successful")
+            elif verb == "MAIL":
+                self.sender, self.recipients = line[10:].strip("<>"), []
+                await self.reply("250 OK")
+            elif verb == "RCPT":
+                self.recipients.append(line[8:].strip("<>"))
+                await self.reply("250 OK")
+            elif verb == "DATA":
+                await self.reply("354 End data with <CR><LF>.<CR><LF>")
+                self.deliver(await self.read_data())
+                await self.reply("250 OK: queued")
+            elif verb in ("RSET", "NOOP"):
+                await self.reply("250 OK")
+            elif verb == "QUIT":
+                await self.reply("221 Bye")
+                break
+            else:
+                await self.reply("502 Command not implemented")
+        self.writer.close()
+
+
+async def main(port, outbox):
+    async def handle(reader, writer):
+        await Session(reader, writer, outbox).run()
+
+    server = await asyncio.start_server(handle, "0.0.0.0", port)
+    print(f"SMTP sink listening on localhost:{port}" + (f", saving to {outbox}" if outbox else ""))
+    sys.stdout.flush()
+    async with server:
+        await server.serve_forever()
+
+
+if __name__ == "__main__":
+    port = int(sys.argv[1]) if len(sys.argv) > 1 else 1025
+    outbox = Path(sys.argv[2]) if len(sys.argv) > 2 else None
+    asyncio.run(main(port, outbox))
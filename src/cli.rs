@@ -0,0 +1,71 @@
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+use crate::configs::server::Environment;
+
+#[derive(Parser)]
+#[command(name = "rust-ack", version, about = "Rust REST API")]
+pub struct Cli {
+    /// Runs the HTTP server when omitted
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
+}
+
+/// Flags that take precedence over the settings file and environment
+#[derive(Args, Default)]
+pub struct ConfigOverrides {
+    /// Settings file [default: $CONFIG_FILE, or etc/config.toml if present]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Listen address, e.g. 127.0.0.1:8080
+    #[arg(long, global = true, value_name = "ADDR")]
+    pub bind: Option<String>,
+
+    /// development or production
+    #[arg(long, global = true)]
+    pub environment: Option<Environment>,
+
+    #[arg(long, global = true, value_name = "URL")]
+    pub database_url: Option<String>,
+
+    #[arg(long, global = true, value_name = "URL")]
+    pub redis_url: Option<String>,
+
+    /// Log filter used when RUST_LOG is unset
+    #[arg(long, global = true, value_name = "FILTER")]
+    pub log_level: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Manage database migrations
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+    /// Inspect the effective configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MigrateAction {
+    /// Apply all pending migrations
+    Up,
+    /// Revert the most recently applied migration
+    Down,
+    /// Show applied and pending migrations
+    Status,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print the effective settings as TOML, secrets redacted
+    Print,
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Token signing and lifetimes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// HMAC key for HS256 tokens
+    pub jwt_secret: String,
+    /// Algorithm new access tokens are signed with
+    pub jwt_algorithm: JwtAlgorithm,
+    /// PEM private key (PKCS#8, or PKCS#1 for RSA) for RS256 and EdDSA
+    pub jwt_private_key_file: Option<String>,
+    /// PEM public key matching `jwt_private_key_file`
+    pub jwt_public_key_file: Option<String>,
+    /// PEM public keys of retired or upcoming signing keys. Tokens signed with
+    /// them still verify, and they are published in the JWKS.
+    pub jwt_verification_key_files: Vec<String>,
+    /// Keep accepting HS256 tokens after switching to RS256 or EdDSA, so tokens
+    /// issued before the switch stay valid until they expire
+    pub jwt_accept_hs256: bool,
+    pub access_token_ttl_secs: i64,
+    pub refresh_token_ttl_secs: i64,
+    /// Issuer shown by authenticator apps for TOTP enrollments
+    pub totp_issuer: String,
+    /// How long a password-verified login may wait for its second factor
+    pub mfa_challenge_ttl_secs: i64,
+    /// Lifetime of a password reset link
+    pub password_reset_ttl_secs: i64,
+    /// Lifetime of an email verification link
+    pub email_verification_ttl_secs: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JwtAlgorithm {
+    /// HMAC with `jwt_secret`; verifiers need the secret
+    #[default]
+    HS256,
+    RS256,
+    /// Ed25519
+    EdDSA,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            jwt_secret: "your-secret-key-change-in-production".to_string(),
+            jwt_algorithm: JwtAlgorithm::HS256,
+            jwt_private_key_file: None,
+            jwt_public_key_file: None,
+            jwt_verification_key_files: Vec::new(),
+            jwt_accept_hs256: false,
+            access_token_ttl_secs: 900,
+            refresh_token_ttl_secs: 604800,
+            totp_issuer: "Rust REST API".to_string(),
+            mfa_challenge_ttl_secs: 300,
+            password_reset_ttl_secs: 1800,
+            email_verification_ttl_secs: 86400,
+        }
+    }
+}
+
+impl AuthConfig {
+    /// Whether tokens signed with `jwt_secret` are issued or accepted
+    pub fn uses_hs256(&self) -> bool {
+        self.jwt_algorithm == JwtAlgorithm::HS256 || self.jwt_accept_hs256
+    }
+}
+
+impl FromStr for JwtAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "HS256" => Ok(JwtAlgorithm::HS256),
+            "RS256" => Ok(JwtAlgorithm::RS256),
+            "EdDSA" => Ok(JwtAlgorithm::EdDSA),
+            _ => Err("expected HS256, RS256 or EdDSA".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for JwtAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            JwtAlgorithm::HS256 => "HS256",
+            JwtAlgorithm::RS256 => "RS256",
+            JwtAlgorithm::EdDSA => "EdDSA",
+        })
+    }
+}
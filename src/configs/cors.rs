@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Origins allowed by the strict (production) CORS policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: vec!["http://localhost:3000".to_string()],
+        }
+    }
+}
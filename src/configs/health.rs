@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Thresholds for the readiness probe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthConfig {
+    /// A dependency that does not answer within this time is reported down
+    pub check_timeout_ms: u64,
+    /// Slower database round trips mark the service degraded
+    pub db_degraded_ms: u64,
+    /// Slower Redis round trips mark the service degraded
+    pub redis_degraded_ms: u64,
+    /// Whether `/readyz` fails (503) while degraded instead of only reporting it
+    pub fail_when_degraded: bool,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            check_timeout_ms: 1000,
+            db_degraded_ms: 250,
+            redis_degraded_ms: 100,
+            fail_when_degraded: false,
+        }
+    }
+}
+
+impl HealthConfig {
+    pub fn check_timeout(&self) -> Duration {
+        Duration::from_millis(self.check_timeout_ms)
+    }
+
+    pub fn db_degraded_after(&self) -> Duration {
+        Duration::from_millis(self.db_degraded_ms)
+    }
+
+    pub fn redis_degraded_after(&self) -> Duration {
+        Duration::from_millis(self.redis_degraded_ms)
+    }
+}
+
+/// Process lifecycle flags reported by the probes
+pub struct ProbeState {
+    created_at: Instant,
+    started: AtomicBool,
+    draining: AtomicBool,
+}
+
+impl ProbeState {
+    pub fn new() -> Self {
+        Self {
+            created_at: Instant::now(),
+            started: AtomicBool::new(false),
+            draining: AtomicBool::new(false),
+        }
+    }
+
+    /// Called once the server is bound and about to accept connections
+    pub fn mark_started(&self) {
+        self.started.store(true, Ordering::Release);
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::Acquire)
+    }
+
+    /// Called when shutdown begins; readiness fails from then on
+    pub fn begin_draining(&self) {
+        self.draining.store(true, Ordering::Release);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Acquire)
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.created_at.elapsed()
+    }
+}
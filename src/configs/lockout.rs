@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Failed-login throttling, per username and per client IP.
+///
+/// Counters apply to any submitted username, existing or not, so responses
+/// never reveal which accounts exist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LockoutConfig {
+    /// How long a failure counts; each new failure restarts the window
+    pub failure_window_secs: u64,
+    /// Failures for one username before each further attempt must wait
+    pub backoff_after: u32,
+    /// First wait; doubles with every further failure
+    pub backoff_base_secs: u64,
+    pub backoff_max_secs: u64,
+    /// Failures for one username that lock it out entirely
+    pub max_failures_per_user: u32,
+    /// Failures from one IP, across usernames, that lock the IP out
+    pub max_failures_per_ip: u32,
+    pub lockout_secs: u64,
+}
+
+impl Default for LockoutConfig {
+    fn default() -> Self {
+        Self {
+            failure_window_secs: 900,
+            backoff_after: 3,
+            backoff_base_secs: 1,
+            backoff_max_secs: 60,
+            max_failures_per_user: 10,
+            max_failures_per_ip: 100,
+            lockout_secs: 900,
+        }
+    }
+}
+
+impl LockoutConfig {
+    /// Wait imposed after the `failures`-th failure for a username, if any
+    pub fn backoff(&self, failures: u32) -> Option<Duration> {
+        let excess = failures.checked_sub(self.backoff_after)?;
+        let secs = self
+            .backoff_base_secs
+            .saturating_mul(1u64.checked_shl(excess).unwrap_or(u64::MAX))
+            .min(self.backoff_max_secs);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
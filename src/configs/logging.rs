@@ -1,25 +1,69 @@
+use opentelemetry::global;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{Protocol, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::{EnvFilter, fmt, prelude::*};
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct LoggingConfig {
     pub log_dir: String,
+    /// Filter directive used when `RUST_LOG` is unset
     pub log_level: String,
     pub rotation: LogRotation,
     pub format: LogFormat,
+    /// OTLP/HTTP collector base URL; spans are only exported when set
+    pub otlp_endpoint: Option<String>,
+    pub service_name: String,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LogRotation {
     Hourly,
     Daily,
     Never,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LogFormat {
     Json,
     Pretty,
     Compact,
 }
 
+impl FromStr for LogRotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hourly" => Ok(LogRotation::Hourly),
+            "daily" => Ok(LogRotation::Daily),
+            "never" => Ok(LogRotation::Never),
+            _ => Err("expected hourly, daily or never".to_string()),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(LogFormat::Json),
+            "pretty" => Ok(LogFormat::Pretty),
+            "compact" => Ok(LogFormat::Compact),
+            _ => Err("expected json, pretty or compact".to_string()),
+        }
+    }
+}
+
 impl Default for LoggingConfig {
     fn default() -> Self {
         Self {
@@ -27,31 +71,16 @@ impl Default for LoggingConfig {
             log_level: "info".to_string(),
             rotation: LogRotation::Daily,
             format: LogFormat::Json,
+            otlp_endpoint: None,
+            service_name: "rust-ack".to_string(),
         }
     }
 }
 
 impl LoggingConfig {
-    pub fn from_env() -> Self {
-        Self {
-            log_dir: std::env::var("LOG_DIR").unwrap_or_else(|_| "logs".to_string()),
-            log_level: std::env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
-            rotation: match std::env::var("LOG_ROTATION").as_deref() {
-                Ok("hourly") => LogRotation::Hourly,
-                Ok("daily") => LogRotation::Daily,
-                Ok("never") => LogRotation::Never,
-                _ => LogRotation::Daily, // Default to Daily
-            },
-            format: match std::env::var("LOG_FORMAT").as_deref() {
-                Ok("json") => LogFormat::Json,
-                Ok("pretty") => LogFormat::Pretty,
-                Ok("compact") => LogFormat::Compact,
-                _ => LogFormat::Json, // Default to JSON
-            },
-        }
-    }
-
-    pub fn init(&self) {
+    /// Install the global subscriber. Returns the tracer provider when OTLP export
+    /// is enabled; shut it down on exit to flush buffered spans.
+    pub fn init(&self) -> Option<SdkTracerProvider> {
         // Create logs directory if it doesn't exist
         std::fs::create_dir_all(&self.log_dir).expect("Failed to create logs directory");
 
@@ -109,11 +138,29 @@ impl LoggingConfig {
             .pretty()
             .boxed();
 
+        // W3C `traceparent` / `tracestate` for incoming and outgoing requests
+        global::set_text_map_propagator(TraceContextPropagator::new());
+
+        // Optional OTLP exporter layer
+        let tracer_provider = self.otlp_endpoint.as_deref().and_then(|endpoint| {
+            match self.tracer_provider(endpoint) {
+                Ok(provider) => Some(provider),
+                Err(e) => {
+                    eprintln!("Failed to create OTLP exporter for {}: {}", endpoint, e);
+                    None
+                }
+            }
+        });
+        let otel_layer = tracer_provider.as_ref().map(|provider| {
+            tracing_opentelemetry::layer().with_tracer(provider.tracer(self.service_name.clone()))
+        });
+
         // Initialize tracing subscriber with both console and file output
         tracing_subscriber::registry()
             .with(env_filter)
             .with(file_layer)
             .with(stdout_layer)
+            .with(otel_layer)
             .init();
 
         tracing::info!("✓ Logging initialized");
@@ -135,5 +182,30 @@ impl LoggingConfig {
                 LogRotation::Never => "Never",
             }
         );
+        if let Some(endpoint) = &self.otlp_endpoint {
+            tracing::info!("  OTLP endpoint: {}", endpoint);
+        }
+
+        tracer_provider
+    }
+
+    fn tracer_provider(
+        &self,
+        endpoint: &str,
+    ) -> Result<SdkTracerProvider, opentelemetry_otlp::ExporterBuildError> {
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_protocol(Protocol::HttpBinary)
+            .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+            .build()?;
+
+        Ok(SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(
+                Resource::builder()
+                    .with_service_name(self.service_name.clone())
+                    .build(),
+            )
+            .build())
     }
 }
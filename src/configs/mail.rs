@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Outgoing mail for password resets and email verification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailConfig {
+    pub transport: MailTransport,
+    /// `From` header, e.g. `Rust REST API <no-reply@example.com>`
+    pub from: String,
+    /// Where the file transport writes `.eml` files
+    pub outbox_dir: String,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_security: SmtpSecurity,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub timeout_secs: u64,
+    /// Front-end URL that reset and verification links point at
+    pub link_base_url: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MailTransport {
+    /// Write messages to `outbox_dir`
+    #[default]
+    File,
+    Smtp,
+}
+
+/// How the SMTP connection is encrypted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Plaintext; only for a local relay or the development SMTP sink
+    #[default]
+    None,
+    /// Upgrade with STARTTLS and refuse to continue without it, usually port 587
+    StartTls,
+    /// TLS from the first byte, usually port 465
+    Tls,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            transport: MailTransport::File,
+            from: "Rust REST API <no-reply@localhost>".to_string(),
+            outbox_dir: "logs/mail".to_string(),
+            smtp_host: "localhost".to_string(),
+            smtp_port: 1025,
+            smtp_security: SmtpSecurity::None,
+            smtp_username: None,
+            smtp_password: None,
+            timeout_secs: 10,
+            link_base_url: "http://localhost:3000".to_string(),
+        }
+    }
+}
+
+impl MailConfig {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// Link to a front-end page carrying a one-time token
+    pub fn link(&self, page: &str, token: &str) -> String {
+        format!(
+            "{}/{}?token={}",
+            self.link_base_url.trim_end_matches('/'),
+            page,
+            token
+        )
+    }
+}
+
+impl FromStr for MailTransport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file" => Ok(MailTransport::File),
+            "smtp" => Ok(MailTransport::Smtp),
+            _ => Err("expected file or smtp".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for MailTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MailTransport::File => "file",
+            MailTransport::Smtp => "smtp",
+        })
+    }
+}
+
+impl FromStr for SmtpSecurity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(SmtpSecurity::None),
+            "starttls" => Ok(SmtpSecurity::StartTls),
+            "tls" => Ok(SmtpSecurity::Tls),
+            _ => Err("expected none, starttls or tls".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for SmtpSecurity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SmtpSecurity::None => "none",
+            SmtpSecurity::StartTls => "starttls",
+            SmtpSecurity::Tls => "tls",
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use sqlx::PgPool;
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+
+/// Versioned migrations from `etc/db/migrations`, embedded at compile time
+static MIGRATOR: Migrator = sqlx::migrate!("etc/db/migrations");
+
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+}
+
+/// Apply every pending migration
+pub async fn run(pool: &PgPool) -> Result<(), MigrateError> {
+    tracing::info!("Running database migrations...");
+    MIGRATOR.run(pool).await?;
+    tracing::info!("✓ Database migrations up to date");
+    Ok(())
+}
+
+/// Revert the most recently applied migration, returning its version
+pub async fn revert_last(pool: &PgPool) -> Result<Option<i64>, MigrateError> {
+    let applied = applied_versions(pool).await?;
+
+    let Some(&last) = applied.last() else {
+        return Ok(None);
+    };
+    // `undo` reverts everything above the target, so aim at the previous version
+    let target = applied.iter().rev().nth(1).copied().unwrap_or(0);
+
+    tracing::info!("Reverting migration {}...", last);
+    MIGRATOR.undo(pool, target).await?;
+    tracing::info!("✓ Reverted migration {}", last);
+
+    Ok(Some(last))
+}
+
+/// Every known migration and whether it has been applied
+pub async fn status(pool: &PgPool) -> Result<Vec<MigrationStatus>, MigrateError> {
+    let applied = applied_versions(pool).await?;
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            applied: applied.contains(&m.version),
+        })
+        .collect())
+}
+
+async fn applied_versions(pool: &PgPool) -> Result<Vec<i64>, MigrateError> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+
+    let mut versions: Vec<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+    versions.sort_unstable();
+
+    Ok(versions)
+}
@@ -1,97 +1,90 @@
+pub mod auth;
+pub mod cors;
 pub mod database;
+pub mod health;
+pub mod lockout;
 pub mod logging;
+pub mod mail;
+pub mod migrations;
+pub mod pagination;
+pub mod production;
+pub mod proxy;
+pub mod rate_limit;
+pub mod server;
+pub mod settings;
+pub mod shutdown;
 
 use redis::aio::ConnectionManager;
 use sqlx::PgPool;
-use std::env;
+use std::sync::Arc;
 
-use database::{DatabaseConfig, RedisConfig};
+use auth::AuthConfig;
+use health::{HealthConfig, ProbeState};
+use lockout::LockoutConfig;
+use mail::MailConfig;
+use pagination::PaginationConfig;
+use proxy::ProxyConfig;
+use settings::Settings;
+
+use crate::jwt::JwtKeys;
+use crate::mail::{self as mailer, Mailer};
+use crate::shutdown::Shutdown;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
     pub redis: ConnectionManager,
-    pub jwt_secret: String,
+    pub auth: AuthConfig,
+    pub jwt: Arc<JwtKeys>,
+    pub lockout: LockoutConfig,
+    pub pagination: PaginationConfig,
+    pub proxy: ProxyConfig,
+    pub health: HealthConfig,
+    pub probes: Arc<ProbeState>,
+    pub shutdown: Shutdown,
+    pub mail: MailConfig,
+    pub mailer: Arc<dyn Mailer>,
 }
 
 impl AppState {
-    pub async fn new() -> Result<Self, Box<dyn std::error::Error>> {
-        let jwt_secret = env::var("JWT_SECRET")
-            .unwrap_or_else(|_| "your-secret-key-change-in-production".to_string());
-
+    pub async fn new(settings: &Settings) -> Result<Self, Box<dyn std::error::Error>> {
         tracing::info!("Initializing application state...");
 
-        let db_config = DatabaseConfig::from_env();
-        let redis_config = RedisConfig::from_env();
+        let jwt = JwtKeys::from_config(&settings.auth)?;
+        match jwt.current_kid() {
+            Some(kid) => tracing::info!(
+                algorithm = %settings.auth.jwt_algorithm,
+                kid,
+                published_keys = jwt.jwks().keys.len(),
+                "✓ JWT signing keys loaded"
+            ),
+            None => tracing::info!("✓ JWT tokens signed with HS256"),
+        }
+
+        let db = settings.database.connect().await?;
+        let redis = settings.redis.connect().await?;
+
+        let mailer = mailer::from_config(&settings.mail)?;
 
-        let db = db_config.connect().await?;
-        let redis = redis_config.connect().await?;
+        if settings.database.run_migrations {
+            migrations::run(&db).await?;
+        }
 
         tracing::info!("✓ Application state initialized successfully");
 
         Ok(AppState {
             db,
             redis,
-            jwt_secret,
+            auth: settings.auth.clone(),
+            jwt: Arc::new(jwt),
+            lockout: settings.lockout.clone(),
+            pagination: settings.pagination.clone(),
+            proxy: settings.proxy.clone(),
+            health: settings.health.clone(),
+            probes: Arc::new(ProbeState::new()),
+            shutdown: Shutdown::new(),
+            mail: settings.mail.clone(),
+            mailer,
         })
     }
-
-    /// Health check for database and Redis connections
-    pub async fn health_check(&self) -> Result<HealthStatus, Box<dyn std::error::Error>> {
-        let mut status = HealthStatus::default();
-
-        // Check PostgreSQL
-        match sqlx::query("SELECT 1").execute(&self.db).await {
-            Ok(_) => {
-                status.database = "healthy".to_string();
-                log::info!("✓ PostgreSQL health check passed");
-            }
-            Err(e) => {
-                status.database = format!("unhealthy: {}", e);
-                log::error!("✗ PostgreSQL health check failed: {}", e);
-            }
-        }
-
-        // Check Redis
-        let mut redis_conn = self.redis.clone();
-        match redis::cmd("PING").query_async::<()>(&mut redis_conn).await {
-            Ok(_) => {
-                status.redis = "healthy".to_string();
-                log::info!("✓ Redis health check passed");
-            }
-            Err(e) => {
-                status.redis = format!("unhealthy: {}", e);
-                log::error!("✗ Redis health check failed: {}", e);
-            }
-        }
-
-        // Set overall status
-        status.status = if status.database == "healthy" && status.redis == "healthy" {
-            "healthy".to_string()
-        } else {
-            "unhealthy".to_string()
-        };
-
-        Ok(status)
-    }
-}
-
-#[derive(Debug, serde::Serialize)]
-#[allow(dead_code)]
-pub struct HealthStatus {
-    pub status: String,
-    pub database: String,
-    pub redis: String,
-    pub timestamp: String,
-}
-
-impl Default for HealthStatus {
-    fn default() -> Self {
-        Self {
-            status: "unknown".to_string(),
-            database: "unknown".to_string(),
-            redis: "unknown".to_string(),
-            timestamp: chrono::Utc::now().to_rfc3339(),
-        }
-    }
 }
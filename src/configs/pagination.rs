@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaginationConfig {
+    pub default_page_size: i64,
+    pub max_page_size: i64,
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self {
+            default_page_size: 20,
+            max_page_size: 100,
+        }
+    }
+}
+
+impl PaginationConfig {
+    /// Requested page size, or the default, capped at the server maximum
+    pub fn page_size(&self, requested: Option<i64>) -> i64 {
+        requested
+            .unwrap_or(self.default_page_size)
+            .clamp(1, self.max_page_size)
+    }
+}
@@ -0,0 +1,95 @@
+use redis::ConnectionAddr;
+use sqlx::postgres::{PgConnectOptions, PgSslMode};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use super::auth::AuthConfig;
+use super::database::DatabaseConfig;
+use super::mail::{MailTransport, SmtpSecurity};
+use super::settings::Settings;
+
+/// Shortest JWT secret accepted in production: 256 bits, as HS256 expects
+const MIN_JWT_SECRET_LEN: usize = 32;
+
+/// Everything that makes `settings` unsafe to run in production.
+///
+/// Only called on settings that already passed validation, so URLs parse.
+pub fn violations(settings: &Settings) -> Vec<String> {
+    let mut violations = Vec::new();
+
+    // The secret is unused once HS256 tokens are neither issued nor accepted
+    let secret = &settings.auth.jwt_secret;
+    if settings.auth.uses_hs256() {
+        if *secret == AuthConfig::default().jwt_secret {
+            violations.push("auth.jwt_secret is the built-in default; set JWT_SECRET".to_string());
+        } else if secret.len() < MIN_JWT_SECRET_LEN {
+            violations.push(format!(
+                "auth.jwt_secret is {} bytes; use at least {}",
+                secret.len(),
+                MIN_JWT_SECRET_LEN
+            ));
+        }
+    }
+
+    if settings.database.url == DatabaseConfig::default().url {
+        violations.push("database.url is the built-in default; set DATABASE_URL".to_string());
+    }
+    if let Ok(options) = PgConnectOptions::from_str(&settings.database.url) {
+        let encrypted = matches!(
+            options.get_ssl_mode(),
+            PgSslMode::Require | PgSslMode::VerifyCa | PgSslMode::VerifyFull
+        );
+        if !encrypted && !is_local(options.get_host()) {
+            violations.push(format!(
+                "database.url connects to {} without TLS; add sslmode=require or stricter",
+                options.get_host()
+            ));
+        }
+    }
+
+    if let Ok(client) = redis::Client::open(settings.redis.url.as_str())
+        && let ConnectionAddr::Tcp(host, _) = &client.get_connection_info().addr
+        && !is_local(host)
+    {
+        violations.push(format!(
+            "redis.url connects to {} in plaintext; use rediss:// or a local TLS tunnel",
+            host
+        ));
+    }
+
+    let origins = &settings.cors.allowed_origins;
+    if origins.is_empty() {
+        violations.push("cors.allowed_origins is empty".to_string());
+    }
+    for origin in origins.iter().filter(|o| !o.starts_with("https://")) {
+        violations.push(format!(
+            "cors.allowed_origins entry {:?} is not https",
+            origin
+        ));
+    }
+
+    let mail = &settings.mail;
+    match mail.transport {
+        MailTransport::File => violations
+            .push("mail.transport is file, so no email is delivered; use smtp".to_string()),
+        MailTransport::Smtp
+            if mail.smtp_security == SmtpSecurity::None && !is_local(&mail.smtp_host) =>
+        {
+            violations.push(format!(
+                "mail.smtp_host {} is reached in plaintext; set mail.smtp_security to starttls or tls",
+                mail.smtp_host
+            ))
+        }
+        MailTransport::Smtp => {}
+    }
+
+    violations
+}
+
+/// Loopback addresses and Unix sockets never leave the host
+fn is_local(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host == "localhost"
+        || host.starts_with('/')
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
@@ -0,0 +1,97 @@
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Named rate-limit policies, the `[rate_limit]` section of the settings file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Clients in these ranges are never rate limited
+    pub exempt_cidrs: Vec<IpNet>,
+    /// Evaluated in order; the first policy matching the request applies
+    pub policies: Vec<RateLimitPolicy>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitPolicy {
+    pub name: String,
+    /// Exact path, or a prefix when it ends with `*`
+    pub path: String,
+    /// HTTP methods the policy applies to; empty means all
+    #[serde(default)]
+    pub methods: Vec<String>,
+    #[serde(default)]
+    pub key: RateLimitKey,
+    pub requests: usize,
+    pub window_secs: u64,
+}
+
+/// What a policy counts requests against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitKey {
+    #[default]
+    Ip,
+    /// `user_id` from a valid bearer token, falling back to the client IP
+    User,
+    /// The `X-API-Key` header, falling back to the client IP
+    ApiKey,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            exempt_cidrs: Vec::new(),
+            policies: vec![
+                RateLimitPolicy {
+                    name: "login".to_string(),
+                    path: "/api/auth/login".to_string(),
+                    methods: vec!["POST".to_string()],
+                    key: RateLimitKey::Ip,
+                    requests: 10,
+                    window_secs: 60,
+                },
+                RateLimitPolicy {
+                    name: "default".to_string(),
+                    path: "*".to_string(),
+                    methods: Vec::new(),
+                    key: RateLimitKey::Ip,
+                    requests: 100,
+                    window_secs: 60,
+                },
+            ],
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn is_exempt(&self, ip: IpAddr) -> bool {
+        self.exempt_cidrs.iter().any(|cidr| cidr.contains(&ip))
+    }
+
+    pub fn find_policy(&self, method: &str, path: &str) -> Option<&RateLimitPolicy> {
+        self.policies
+            .iter()
+            .find(|policy| policy.matches(method, path))
+    }
+}
+
+impl RateLimitPolicy {
+    fn matches(&self, method: &str, path: &str) -> bool {
+        let method_matches =
+            self.methods.is_empty() || self.methods.iter().any(|m| m.eq_ignore_ascii_case(method));
+
+        let path_matches = match self.path.strip_suffix('*') {
+            Some(prefix) => path.starts_with(prefix),
+            None => path == self.path,
+        };
+
+        method_matches && path_matches
+    }
+
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Timings for the phases of a graceful shutdown
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownConfig {
+    /// How long `/readyz` reports draining before the listener closes, so load
+    /// balancers stop routing new traffic here first
+    pub readiness_delay_secs: u64,
+    /// How long in-flight HTTP requests get to finish before being dropped
+    pub drain_timeout_secs: u64,
+    /// How long cancelled background tasks get to wind down
+    pub task_timeout_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            readiness_delay_secs: 5,
+            drain_timeout_secs: 30,
+            task_timeout_secs: 10,
+        }
+    }
+}
+
+impl ShutdownConfig {
+    pub fn readiness_delay(&self) -> Duration {
+        Duration::from_secs(self.readiness_delay_secs)
+    }
+
+    pub fn task_timeout(&self) -> Duration {
+        Duration::from_secs(self.task_timeout_secs)
+    }
+}
@@ -0,0 +1,98 @@
+use actix_web::{HttpResponse, web};
+
+use crate::configs::AppState;
+use crate::errors::{AppError, ProblemDetails};
+use crate::middleware::{AuthUser, ClientInfo};
+use crate::models::{RecoveryCodes, TotpCodeDto, TotpEnrollment};
+use crate::services::{AccountService, TotpService, UserService};
+
+/// Start TOTP enrollment for the caller. Returns a secret and an `otpauth://` URI;
+/// 2FA stays off until a code is confirmed. Restarting replaces a pending secret.
+#[utoipa::path(
+    post,
+    path = "/api/account/2fa/enroll",
+    tag = "Account",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Pending enrollment", body = TotpEnrollment),
+        (status = 409, description = "2FA already enabled", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn enroll_totp(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+) -> Result<HttpResponse, AppError> {
+    let enrollment = TotpService::enroll(&state, auth.user_id, &auth.claims.sub).await?;
+    Ok(HttpResponse::Ok().json(enrollment))
+}
+
+/// Enable 2FA by confirming the first authenticator code. The recovery codes
+/// in the response are shown only this once.
+#[utoipa::path(
+    post,
+    path = "/api/account/2fa/confirm",
+    tag = "Account",
+    security(("bearer_auth" = [])),
+    request_body = TotpCodeDto,
+    responses(
+        (status = 200, description = "2FA enabled", body = RecoveryCodes),
+        (status = 400, description = "No pending enrollment, or wrong code", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "2FA already enabled", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn confirm_totp(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+    dto: web::Json<TotpCodeDto>,
+) -> Result<HttpResponse, AppError> {
+    let codes = TotpService::confirm(&state, auth.user_id, &dto.code).await?;
+    Ok(HttpResponse::Ok().json(codes))
+}
+
+/// Turn 2FA off with a current authenticator code or a recovery code.
+/// Wrong codes back off and lock out like wrong passwords.
+#[utoipa::path(
+    post,
+    path = "/api/account/2fa/disable",
+    tag = "Account",
+    security(("bearer_auth" = [])),
+    request_body = TotpCodeDto,
+    responses(
+        (status = 200, description = "2FA disabled", body = serde_json::Value,
+            example = json!({"message": "Two-factor authentication disabled"})),
+        (status = 400, description = "2FA not enabled", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Invalid code", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 429, description = "Too many failed attempts for this username or IP; see `Retry-After`", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn disable_totp(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+    client: ClientInfo,
+    dto: web::Json<TotpCodeDto>,
+) -> Result<HttpResponse, AppError> {
+    TotpService::disable(&state, auth.user_id, &auth.claims.sub, client.ip, &dto.code).await?;
+    Ok(HttpResponse::Ok()
+        .json(serde_json::json!({"message": "Two-factor authentication disabled"})))
+}
+
+/// Send a new verification link to the caller's current email
+#[utoipa::path(
+    post,
+    path = "/api/account/email/verification",
+    tag = "Account",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 202, description = "Verification email sent", body = serde_json::Value,
+            example = json!({"message": "Verification email sent"})),
+        (status = 409, description = "Email already verified", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn request_email_verification(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+) -> Result<HttpResponse, AppError> {
+    let user = UserService::get_by_id(&state.db, auth.user_id).await?;
+    AccountService::send_verification(&state, &user).await?;
+    Ok(HttpResponse::Accepted().json(serde_json::json!({"message": "Verification email sent"})))
+}
@@ -1,31 +1,135 @@
-use actix_web::{Error, HttpRequest, HttpResponse, web};
+use actix_web::http::header::CACHE_CONTROL;
+use actix_web::{HttpRequest, HttpResponse, web};
 
 use crate::configs::AppState;
-use crate::models::LoginDto;
-use crate::services::AuthService;
+use crate::errors::{AppError, ProblemDetails};
+use crate::middleware::{ClientInfo, ClientIp};
+use crate::models::{
+    ForgotPasswordDto, LoginDto, LoginResponse, MfaLoginDto, RefreshTokenDto, ResetPasswordDto,
+    TokenResponse, VerifyEmailDto,
+};
+use crate::services::{AccountService, AuthService};
+use crate::validation::ValidatedJson;
 
+/// Exchange credentials for an access and refresh token pair. Users with
+/// two-factor authentication get an MFA challenge instead. Repeated failures
+/// back off exponentially, then lock the username or IP out for a while.
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "Authentication",
+    request_body = LoginDto,
+    responses(
+        (status = 200, description = "Authenticated, or a second factor is required", body = LoginResponse),
+        (status = 401, description = "Invalid credentials", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 429, description = "Too many failed attempts for this username or IP; see `Retry-After`", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn login(
     state: web::Data<AppState>,
+    client_ip: ClientIp,
+    client: ClientInfo,
     dto: web::Json<LoginDto>,
-) -> Result<HttpResponse, Error> {
-    tracing::info!("Login attempt for user: {}", dto.username);
+) -> Result<HttpResponse, AppError> {
+    let username = dto.username.clone();
+    tracing::info!(ip = %client_ip, "Login attempt for user: {}", username);
 
-    match AuthService::login(&state, dto.into_inner()).await {
+    match AuthService::login(&state, dto.into_inner(), &client).await {
+        Ok(response) => {
+            match &response {
+                LoginResponse::Tokens(_) => {
+                    tracing::info!(ip = %client_ip, "Login successful for user: {}", username)
+                }
+                LoginResponse::MfaRequired(_) => {
+                    tracing::info!(ip = %client_ip, "Second factor required for user: {}", username)
+                }
+            }
+            Ok(HttpResponse::Ok().json(response))
+        }
+        Err(e) => {
+            tracing::warn!(ip = %client_ip, "Login failed: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// Complete a two-factor login with an authenticator or recovery code.
+/// Five wrong codes revoke the challenge, and wrong codes back off and lock
+/// out like wrong passwords.
+#[utoipa::path(
+    post,
+    path = "/api/auth/login/mfa",
+    tag = "Authentication",
+    request_body = MfaLoginDto,
+    responses(
+        (status = 200, description = "Authenticated", body = TokenResponse),
+        (status = 401, description = "Invalid code, or invalid or expired challenge", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 429, description = "Too many failed attempts for this username or IP; see `Retry-After`", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn login_mfa(
+    state: web::Data<AppState>,
+    client_ip: ClientIp,
+    client: ClientInfo,
+    dto: web::Json<MfaLoginDto>,
+) -> Result<HttpResponse, AppError> {
+    match AuthService::login_mfa(&state, dto.into_inner(), &client).await {
+        Ok(token) => {
+            tracing::info!(ip = %client_ip, "Two-factor login successful");
+            Ok(HttpResponse::Ok().json(token))
+        }
+        Err(e) => {
+            tracing::warn!(ip = %client_ip, "Two-factor login failed: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// Rotate a refresh token. Reusing a rotated token revokes its whole token family.
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    tag = "Authentication",
+    request_body = RefreshTokenDto,
+    responses(
+        (status = 200, description = "New token pair", body = TokenResponse),
+        (status = 401, description = "Invalid, expired or reused refresh token", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn refresh(
+    state: web::Data<AppState>,
+    client: ClientInfo,
+    dto: web::Json<RefreshTokenDto>,
+) -> Result<HttpResponse, AppError> {
+    tracing::info!("Token refresh attempt");
+
+    match AuthService::refresh(&state, &dto.refresh_token, &client).await {
         Ok(token) => {
-            tracing::info!(
-                "Login successful for user: {}",
-                token.token.chars().take(10).collect::<String>()
-            );
+            tracing::info!("Token refresh successful");
             Ok(HttpResponse::Ok().json(token))
         }
         Err(e) => {
-            tracing::warn!("Login failed: {}", e);
-            Ok(HttpResponse::Unauthorized().json(serde_json::json!({"error": e})))
+            tracing::warn!("Token refresh failed: {}", e);
+            Err(e)
         }
     }
 }
 
-pub async fn logout(state: web::Data<AppState>, req: HttpRequest) -> Result<HttpResponse, Error> {
+/// Revoke the presented access token and its refresh token family
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "Authentication",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Logged out", body = serde_json::Value,
+            example = json!({"message": "Logged out successfully"})),
+    )
+)]
+pub async fn logout(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
     let token = req
         .headers()
         .get("Authorization")
@@ -35,14 +139,107 @@ pub async fn logout(state: web::Data<AppState>, req: HttpRequest) -> Result<Http
 
     tracing::info!("Logout attempt");
 
-    match AuthService::logout(&state, token).await {
-        Ok(_) => {
-            tracing::info!("Logout successful");
-            Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Logged out successfully"})))
+    AuthService::logout(&state, token).await?;
+
+    tracing::info!("Logout successful");
+    Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Logged out successfully"})))
+}
+
+/// Email a password reset link. Always accepted, whether or not the address
+/// belongs to an account.
+#[utoipa::path(
+    post,
+    path = "/api/auth/password/forgot",
+    tag = "Authentication",
+    request_body = ForgotPasswordDto,
+    responses(
+        (status = 202, description = "A reset link is sent if the email is registered", body = serde_json::Value,
+            example = json!({"message": "If the email is registered, a reset link has been sent"})),
+        (status = 422, description = "Field validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn forgot_password(
+    state: web::Data<AppState>,
+    client_ip: ClientIp,
+    dto: ValidatedJson<ForgotPasswordDto>,
+) -> Result<HttpResponse, AppError> {
+    tracing::info!(ip = %client_ip, "Password reset requested");
+
+    AccountService::forgot_password(&state, &dto.email).await?;
+
+    Ok(HttpResponse::Accepted().json(
+        serde_json::json!({"message": "If the email is registered, a reset link has been sent"}),
+    ))
+}
+
+/// Choose a new password with a token from a reset email. Every existing
+/// session of the user is revoked.
+#[utoipa::path(
+    post,
+    path = "/api/auth/password/reset",
+    tag = "Authentication",
+    request_body = ResetPasswordDto,
+    responses(
+        (status = 200, description = "Password changed", body = serde_json::Value,
+            example = json!({"message": "Password has been reset"})),
+        (status = 400, description = "Invalid or expired reset token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Field validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn reset_password(
+    state: web::Data<AppState>,
+    client_ip: ClientIp,
+    dto: ValidatedJson<ResetPasswordDto>,
+) -> Result<HttpResponse, AppError> {
+    match AccountService::reset_password(&state, &dto.token, &dto.new_password).await {
+        Ok(()) => {
+            tracing::info!(ip = %client_ip, "Password reset successful");
+            Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Password has been reset"})))
         }
         Err(e) => {
-            tracing::error!("Logout failed: {}", e);
-            Ok(HttpResponse::BadRequest().json(serde_json::json!({"error": e})))
+            tracing::warn!(ip = %client_ip, "Password reset failed: {}", e);
+            Err(e)
         }
     }
 }
+
+/// Confirm an email address with a token from a verification email
+#[utoipa::path(
+    post,
+    path = "/api/auth/email/verify",
+    tag = "Authentication",
+    request_body = VerifyEmailDto,
+    responses(
+        (status = 200, description = "Email verified", body = serde_json::Value,
+            example = json!({"message": "Email verified"})),
+        (status = 400, description = "Invalid or expired verification token", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn verify_email(
+    state: web::Data<AppState>,
+    dto: ValidatedJson<VerifyEmailDto>,
+) -> Result<HttpResponse, AppError> {
+    AccountService::verify_email(&state, &dto.token).await?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Email verified"})))
+}
+
+/// Public keys that verify access tokens, as a JWK Set (RFC 7517). Match a
+/// token's `kid` header to a key's `kid`. HS256 keys are never published.
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    tag = "Authentication",
+    responses(
+        (status = 200, description = "JWK Set", body = serde_json::Value,
+            content_type = "application/jwk-set+json",
+            example = json!({"keys": [{"kty": "OKP", "crv": "Ed25519", "x": "zFMATxoHcQ4vXmyewaiP1E88KvtN5F0Wk6LhoGtP4PQ",
+                "use": "sig", "alg": "EdDSA", "kid": "QI6IXYicj1XJ2no9v93YlXC5ltRdJyLDy93q-EZkYRw"}]})),
+    )
+)]
+pub async fn jwks(state: web::Data<AppState>) -> HttpResponse {
+    // Short enough that a newly staged key is picked up well before it signs
+    HttpResponse::Ok()
+        .content_type("application/jwk-set+json")
+        .insert_header((CACHE_CONTROL, "public, max-age=300"))
+        .json(state.jwt.jwks())
+}
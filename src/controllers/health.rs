@@ -0,0 +1,72 @@
+use actix_web::{HttpResponse, web};
+
+use crate::configs::AppState;
+use crate::models::health::{ProbeStatus, Readiness, ReadinessStatus};
+use crate::services::HealthService;
+
+fn probe_status(state: &AppState, status: &'static str) -> ProbeStatus {
+    ProbeStatus {
+        status,
+        uptime_secs: state.probes.uptime().as_secs(),
+        version: env!("CARGO_PKG_VERSION"),
+    }
+}
+
+/// Liveness: the process is running and can answer HTTP. Never checks dependencies,
+/// so an outage of PostgreSQL or Redis does not get the pod restarted.
+#[utoipa::path(
+    get,
+    path = "/livez",
+    tag = "System",
+    responses(
+        (status = 200, description = "Process is alive", body = ProbeStatus),
+    )
+)]
+pub async fn livez(state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(probe_status(&state, "ok"))
+}
+
+/// Startup: initialisation (connections, migrations) has finished
+#[utoipa::path(
+    get,
+    path = "/startupz",
+    tag = "System",
+    responses(
+        (status = 200, description = "Startup complete", body = ProbeStatus),
+        (status = 503, description = "Still starting", body = ProbeStatus),
+    )
+)]
+pub async fn startupz(state: web::Data<AppState>) -> HttpResponse {
+    if state.probes.is_started() {
+        HttpResponse::Ok().json(probe_status(&state, "ok"))
+    } else {
+        HttpResponse::ServiceUnavailable().json(probe_status(&state, "starting"))
+    }
+}
+
+/// Readiness: PostgreSQL and Redis are reachable, with per-dependency latency and
+/// pool statistics. A degraded service stays ready unless `READINESS_FAIL_WHEN_DEGRADED=true`.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "System",
+    responses(
+        (status = 200, description = "Ready, or degraded but still serving", body = Readiness),
+        (status = 503, description = "A dependency is down, or the server is shutting down", body = Readiness),
+    )
+)]
+pub async fn readyz(state: web::Data<AppState>) -> HttpResponse {
+    let readiness = HealthService::readiness(&state).await;
+
+    let serving = match readiness.status {
+        ReadinessStatus::Ready => true,
+        ReadinessStatus::Degraded => !state.health.fail_when_degraded,
+        ReadinessStatus::Unavailable | ReadinessStatus::Draining => false,
+    };
+
+    if serving {
+        HttpResponse::Ok().json(readiness)
+    } else {
+        HttpResponse::ServiceUnavailable().json(readiness)
+    }
+}
@@ -0,0 +1,54 @@
+use actix_web::{HttpResponse, web};
+use std::net::IpAddr;
+
+use crate::configs::AppState;
+use crate::errors::{AppError, ProblemDetails};
+use crate::middleware::AuthUser;
+use crate::services::LockoutService;
+
+/// Lift a username's login lockout and backoff and reset its failure count.
+/// Requires `users:write`.
+#[utoipa::path(
+    delete,
+    path = "/api/admin/lockouts/users/{username}",
+    tag = "Users",
+    security(("bearer_auth" = [])),
+    params(
+        ("username" = String, Path, description = "Username as submitted at login"),
+    ),
+    responses(
+        (status = 204, description = "Lockout lifted, or none was active"),
+    )
+)]
+pub async fn unlock_user(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+    username: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    LockoutService::unlock_user(&state, &username, &auth.claims.sub).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Lift a client IP's login lockout and reset its failure count.
+/// Requires `users:write`.
+#[utoipa::path(
+    delete,
+    path = "/api/admin/lockouts/ips/{ip}",
+    tag = "Users",
+    security(("bearer_auth" = [])),
+    params(
+        ("ip" = String, Path, description = "IPv4 or IPv6 address"),
+    ),
+    responses(
+        (status = 204, description = "Lockout lifted, or none was active"),
+        (status = 400, description = "Malformed IP address", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn unlock_ip(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+    ip: web::Path<IpAddr>,
+) -> Result<HttpResponse, AppError> {
+    LockoutService::unlock_ip(&state, ip.into_inner(), &auth.claims.sub).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,3 +1,8 @@
+pub mod account;
 pub mod auth;
+pub mod health;
+pub mod lockout;
 pub mod product;
+pub mod role;
+pub mod session;
 pub mod user;
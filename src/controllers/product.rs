@@ -1,68 +1,139 @@
-use actix_web::{Error, HttpRequest, HttpResponse, web};
+use actix_web::{HttpResponse, web};
 use uuid::Uuid;
 
 use crate::configs::AppState;
-use crate::models::{CreateProductDto, ProductQuery, UpdateProductDto};
-use crate::services::{AuthService, ProductService};
+use crate::errors::{AppError, ProblemDetails};
+use crate::middleware::AuthUser;
+use crate::models::{CreateProductDto, Page, Product, ProductQuery, UpdateProductDto};
+use crate::services::ProductService;
+use crate::validation::ValidatedJson;
 
+/// Create a product owned by the caller. Requires `products:write`.
+#[utoipa::path(
+    post,
+    path = "/api/products",
+    tag = "Products",
+    security(("bearer_auth" = [])),
+    request_body = CreateProductDto,
+    responses(
+        (status = 201, description = "Product created", body = Product),
+        (status = 400, description = "Malformed JSON body", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Field validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn create_product(
     state: web::Data<AppState>,
-    req: HttpRequest,
-    dto: web::Json<CreateProductDto>,
-) -> Result<HttpResponse, Error> {
-    let token = req
-        .headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|h| h.strip_prefix("Bearer "))
-        .unwrap_or("");
-
-    let claims = AuthService::validate_token(&state, token).await.unwrap();
-    let user_id = Uuid::parse_str(&claims.user_id).unwrap();
-
-    match ProductService::create(&state.db, dto.into_inner(), user_id).await {
-        Ok(product) => Ok(HttpResponse::Created().json(product)),
-        Err(e) => Ok(HttpResponse::BadRequest().json(serde_json::json!({"error": e}))),
-    }
+    auth: AuthUser,
+    dto: ValidatedJson<CreateProductDto>,
+) -> Result<HttpResponse, AppError> {
+    let product = ProductService::create(&state.db, dto.into_inner(), auth.user_id).await?;
+    Ok(HttpResponse::Created().json(product))
 }
 
+/// Fetch a product by id. Requires `products:read`.
+#[utoipa::path(
+    get,
+    path = "/api/products/{id}",
+    tag = "Products",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "Product id"),
+    ),
+    responses(
+        (status = 200, description = "Product", body = Product),
+        (status = 400, description = "Malformed id", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Product not found", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn get_product(
     state: web::Data<AppState>,
     id: web::Path<Uuid>,
-) -> Result<HttpResponse, Error> {
-    match ProductService::get_by_id(&state.db, id.into_inner()).await {
-        Ok(product) => Ok(HttpResponse::Ok().json(product)),
-        Err(e) => Ok(HttpResponse::NotFound().json(serde_json::json!({"error": e}))),
-    }
+) -> Result<HttpResponse, AppError> {
+    let product = ProductService::get_by_id(&state.db, id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(product))
 }
 
+/// Search products by name, price and stock, one keyset page at a time.
+/// All filters are optional and combined with AND. Requires `products:read`.
+#[utoipa::path(
+    get,
+    path = "/api/products",
+    tag = "Products",
+    security(("bearer_auth" = [])),
+    params(ProductQuery),
+    responses(
+        (status = 200, description = "Page of matching products", body = Page<Product>),
+        (status = 400, description = "Invalid filter, unknown sort column or invalid cursor", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn search_products(
     state: web::Data<AppState>,
     query: web::Query<ProductQuery>,
-) -> Result<HttpResponse, Error> {
-    match ProductService::search(&state.db, query.into_inner()).await {
-        Ok(products) => Ok(HttpResponse::Ok().json(products)),
-        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({"error": e}))),
-    }
+) -> Result<HttpResponse, AppError> {
+    let products = ProductService::search(&state.db, &state.pagination, query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(products))
 }
 
+/// Update a product. Only its creator or an admin may do so. Requires `products:write`.
+#[utoipa::path(
+    put,
+    path = "/api/products/{id}",
+    tag = "Products",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "Product id"),
+    ),
+    request_body = UpdateProductDto,
+    responses(
+        (status = 200, description = "Updated product", body = Product),
+        (status = 400, description = "Malformed id or JSON body", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Product not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Field validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn update_product(
     state: web::Data<AppState>,
+    auth: AuthUser,
     id: web::Path<Uuid>,
-    dto: web::Json<UpdateProductDto>,
-) -> Result<HttpResponse, Error> {
-    match ProductService::update(&state.db, id.into_inner(), dto.into_inner()).await {
-        Ok(product) => Ok(HttpResponse::Ok().json(product)),
-        Err(e) => Ok(HttpResponse::BadRequest().json(serde_json::json!({"error": e}))),
-    }
+    dto: ValidatedJson<UpdateProductDto>,
+) -> Result<HttpResponse, AppError> {
+    let product = ProductService::update(
+        &state.db,
+        id.into_inner(),
+        dto.into_inner(),
+        auth.user_id,
+        auth.claims.has_role("admin"),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(product))
 }
 
+/// Delete a product. Only its creator or an admin may do so. Requires `products:delete`.
+#[utoipa::path(
+    delete,
+    path = "/api/products/{id}",
+    tag = "Products",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "Product id"),
+    ),
+    responses(
+        (status = 204, description = "Product deleted"),
+        (status = 400, description = "Malformed id", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Product not found", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn delete_product(
     state: web::Data<AppState>,
+    auth: AuthUser,
     id: web::Path<Uuid>,
-) -> Result<HttpResponse, Error> {
-    match ProductService::delete(&state.db, id.into_inner()).await {
-        Ok(_) => Ok(HttpResponse::NoContent().finish()),
-        Err(e) => Ok(HttpResponse::BadRequest().json(serde_json::json!({"error": e}))),
-    }
+) -> Result<HttpResponse, AppError> {
+    ProductService::delete(
+        &state.db,
+        id.into_inner(),
+        auth.user_id,
+        auth.claims.has_role("admin"),
+    )
+    .await?;
+    Ok(HttpResponse::NoContent().finish())
 }
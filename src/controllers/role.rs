@@ -0,0 +1,118 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::configs::AppState;
+use crate::errors::{AppError, ProblemDetails};
+use crate::middleware::{AuthUser, ClientIp};
+use crate::models::{AssignRoleDto, Role};
+use crate::services::RoleService;
+
+/// List every role. Requires `roles:read`.
+#[utoipa::path(
+    get,
+    path = "/api/admin/roles",
+    tag = "Roles",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "All roles", body = Vec<Role>),
+    )
+)]
+pub async fn get_all_roles(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    let roles = RoleService::get_all(&state.db).await?;
+    Ok(HttpResponse::Ok().json(roles))
+}
+
+/// List the roles granted to a user. Requires `roles:read`.
+#[utoipa::path(
+    get,
+    path = "/api/admin/users/{id}/roles",
+    tag = "Roles",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "User id"),
+    ),
+    responses(
+        (status = 200, description = "Roles of the user", body = Vec<Role>),
+        (status = 400, description = "Malformed id", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_user_roles(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let roles = RoleService::get_by_user(&state.db, id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(roles))
+}
+
+/// Grant a role to a user. Takes effect on the user's next login or refresh.
+/// Requires `roles:write`.
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/roles",
+    tag = "Roles",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "User id"),
+    ),
+    request_body = AssignRoleDto,
+    responses(
+        (status = 200, description = "Roles of the user after the grant", body = Vec<Role>),
+        (status = 400, description = "Malformed id or JSON body", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "User or role not found", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn assign_role(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+    client_ip: ClientIp,
+    id: web::Path<Uuid>,
+    dto: web::Json<AssignRoleDto>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = id.into_inner();
+
+    let roles = RoleService::assign(&state.db, user_id, &dto.role).await?;
+    tracing::info!(
+        user_id = %user_id,
+        role = %dto.role,
+        assigned_by = %auth.claims.sub,
+        ip = %client_ip,
+        "Role assigned"
+    );
+    Ok(HttpResponse::Ok().json(roles))
+}
+
+/// Revoke a role from a user. Requires `roles:write`.
+#[utoipa::path(
+    delete,
+    path = "/api/admin/users/{id}/roles/{role}",
+    tag = "Roles",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "User id"),
+        ("role" = String, Path, description = "Role name"),
+    ),
+    responses(
+        (status = 204, description = "Role revoked"),
+        (status = 400, description = "Malformed id", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Role not found", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn revoke_role(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+    client_ip: ClientIp,
+    path: web::Path<(Uuid, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, role) = path.into_inner();
+
+    RoleService::revoke(&state.db, user_id, &role).await?;
+    tracing::info!(
+        user_id = %user_id,
+        role = %role,
+        revoked_by = %auth.claims.sub,
+        ip = %client_ip,
+        "Role revoked"
+    );
+    Ok(HttpResponse::NoContent().finish())
+}
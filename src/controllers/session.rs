@@ -0,0 +1,125 @@
+use actix_web::{HttpResponse, web};
+use uuid::Uuid;
+
+use crate::configs::AppState;
+use crate::errors::{AppError, ProblemDetails};
+use crate::middleware::AuthUser;
+use crate::models::{RevokeSessionsQuery, Session};
+use crate::services::SessionService;
+
+/// List the caller's active sessions, most recently active first
+#[utoipa::path(
+    get,
+    path = "/api/auth/sessions",
+    tag = "Authentication",
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, description = "Active sessions", body = Vec<Session>),
+    )
+)]
+pub async fn list_sessions(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+) -> Result<HttpResponse, AppError> {
+    let sessions = SessionService::list(&state, auth.user_id, Some(&auth.claims.family_id)).await?;
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+/// Sign out one of the caller's sessions, e.g. a lost device
+#[utoipa::path(
+    delete,
+    path = "/api/auth/sessions/{id}",
+    tag = "Authentication",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = String, Path, description = "Session id"),
+    ),
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 404, description = "No such session for the caller", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn revoke_session(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+    id: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    SessionService::revoke(&state, auth.user_id, &id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Sign the caller out everywhere, or everywhere else with `except_current=true`
+#[utoipa::path(
+    delete,
+    path = "/api/auth/sessions",
+    tag = "Authentication",
+    security(("bearer_auth" = [])),
+    params(RevokeSessionsQuery),
+    responses(
+        (status = 200, description = "Sessions revoked", body = serde_json::Value,
+            example = json!({"revoked": 3})),
+    )
+)]
+pub async fn revoke_all_sessions(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+    query: web::Query<RevokeSessionsQuery>,
+) -> Result<HttpResponse, AppError> {
+    let except = query
+        .except_current
+        .then_some(auth.claims.family_id.as_str());
+    let revoked = SessionService::revoke_all(&state, auth.user_id, except).await?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "revoked": revoked })))
+}
+
+/// List a user's active sessions. Requires `sessions:read`, held only by admins.
+#[utoipa::path(
+    get,
+    path = "/api/admin/users/{id}/sessions",
+    tag = "Users",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "User id"),
+    ),
+    responses(
+        (status = 200, description = "Active sessions", body = Vec<Session>),
+        (status = 400, description = "Malformed id", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn list_user_sessions(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let sessions = SessionService::list(&state, id.into_inner(), None).await?;
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+/// Sign a user out everywhere. Requires `users:write`.
+#[utoipa::path(
+    delete,
+    path = "/api/admin/users/{id}/sessions",
+    tag = "Users",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "User id"),
+    ),
+    responses(
+        (status = 200, description = "Sessions revoked", body = serde_json::Value,
+            example = json!({"revoked": 3})),
+        (status = 400, description = "Malformed id", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn revoke_user_sessions(
+    state: web::Data<AppState>,
+    auth: AuthUser,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = id.into_inner();
+    let revoked = SessionService::revoke_all(&state, user_id, None).await?;
+    tracing::info!(
+        user_id = %user_id,
+        revoked_by = %auth.claims.sub,
+        "Sessions revoked by admin"
+    );
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "revoked": revoked })))
+}
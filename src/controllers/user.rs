@@ -1,54 +1,132 @@
-use actix_web::{Error, HttpResponse, web};
+use actix_web::{HttpResponse, web};
 use uuid::Uuid;
 
 use crate::configs::AppState;
-use crate::models::{CreateUserDto, UpdateUserDto};
-use crate::services::UserService;
+use crate::errors::{AppError, ProblemDetails};
+use crate::models::{CreateUserDto, Page, UpdateUserDto, User, UserQuery};
+use crate::services::{AccountService, UserService};
+use crate::validation::ValidatedJson;
 
+/// Create a user with the default role and email them a verification link.
+/// Requires `users:write`.
+#[utoipa::path(
+    post,
+    path = "/api/users",
+    tag = "Users",
+    security(("bearer_auth" = [])),
+    request_body = CreateUserDto,
+    responses(
+        (status = 201, description = "User created", body = User),
+        (status = 400, description = "Malformed JSON body", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Username or email already taken", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Field validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn create_user(
     state: web::Data<AppState>,
-    dto: web::Json<CreateUserDto>,
-) -> Result<HttpResponse, Error> {
-    match UserService::create(&state.db, dto.into_inner()).await {
-        Ok(user) => Ok(HttpResponse::Created().json(user)),
-        Err(e) => Ok(HttpResponse::BadRequest().json(serde_json::json!({"error": e}))),
+    dto: ValidatedJson<CreateUserDto>,
+) -> Result<HttpResponse, AppError> {
+    let user = UserService::create(&state.db, dto.into_inner()).await?;
+
+    // The user exists either way; they can ask for another link later
+    if let Err(e) = AccountService::send_verification(&state, &user).await {
+        tracing::warn!(user_id = %user.id, "Failed to send verification email: {}", e);
     }
+
+    Ok(HttpResponse::Created().json(user))
 }
 
+/// Fetch a user by id. Requires `users:read`.
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}",
+    tag = "Users",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "User id"),
+    ),
+    responses(
+        (status = 200, description = "User", body = User),
+        (status = 400, description = "Malformed id", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn get_user(
     state: web::Data<AppState>,
     id: web::Path<Uuid>,
-) -> Result<HttpResponse, Error> {
-    match UserService::get_by_id(&state.db, id.into_inner()).await {
-        Ok(user) => Ok(HttpResponse::Ok().json(user)),
-        Err(e) => Ok(HttpResponse::NotFound().json(serde_json::json!({"error": e}))),
-    }
+) -> Result<HttpResponse, AppError> {
+    let user = UserService::get_by_id(&state.db, id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(user))
 }
 
-pub async fn get_all_users(state: web::Data<AppState>) -> Result<HttpResponse, Error> {
-    match UserService::get_all(&state.db).await {
-        Ok(users) => Ok(HttpResponse::Ok().json(users)),
-        Err(e) => Ok(HttpResponse::InternalServerError().json(serde_json::json!({"error": e}))),
-    }
+/// List users one keyset page at a time. Requires `users:read`.
+#[utoipa::path(
+    get,
+    path = "/api/users",
+    tag = "Users",
+    security(("bearer_auth" = [])),
+    params(UserQuery),
+    responses(
+        (status = 200, description = "Page of users", body = Page<User>),
+        (status = 400, description = "Unknown sort column or invalid cursor", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_all_users(
+    state: web::Data<AppState>,
+    query: web::Query<UserQuery>,
+) -> Result<HttpResponse, AppError> {
+    let users = UserService::get_all(&state.db, &state.pagination, query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(users))
 }
 
+/// Update the given fields of a user. Changing the password revokes all of
+/// the user's sessions. Requires `users:write`.
+#[utoipa::path(
+    put,
+    path = "/api/users/{id}",
+    tag = "Users",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "User id"),
+    ),
+    request_body = UpdateUserDto,
+    responses(
+        (status = 200, description = "Updated user", body = User),
+        (status = 400, description = "Malformed id or JSON body", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Username or email already taken", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Field validation failed", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn update_user(
     state: web::Data<AppState>,
     id: web::Path<Uuid>,
-    dto: web::Json<UpdateUserDto>,
-) -> Result<HttpResponse, Error> {
-    match UserService::update(&state.db, id.into_inner(), dto.into_inner()).await {
-        Ok(user) => Ok(HttpResponse::Ok().json(user)),
-        Err(e) => Ok(HttpResponse::BadRequest().json(serde_json::json!({"error": e}))),
-    }
+    dto: ValidatedJson<UpdateUserDto>,
+) -> Result<HttpResponse, AppError> {
+    let user = UserService::update(&state, id.into_inner(), dto.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(user))
 }
 
+/// Delete a user and revoke all of their sessions. Requires `users:delete`.
+#[utoipa::path(
+    delete,
+    path = "/api/users/{id}",
+    tag = "Users",
+    security(("bearer_auth" = [])),
+    params(
+        ("id" = Uuid, Path, description = "User id"),
+    ),
+    responses(
+        (status = 204, description = "User deleted"),
+        (status = 400, description = "Malformed id", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "User is still referenced", body = ProblemDetails, content_type = "application/problem+json"),
+    )
+)]
 pub async fn delete_user(
     state: web::Data<AppState>,
     id: web::Path<Uuid>,
-) -> Result<HttpResponse, Error> {
-    match UserService::delete(&state.db, id.into_inner()).await {
-        Ok(_) => Ok(HttpResponse::NoContent().finish()),
-        Err(e) => Ok(HttpResponse::BadRequest().json(serde_json::json!({"error": e}))),
-    }
+) -> Result<HttpResponse, AppError> {
+    UserService::delete(&state, id.into_inner()).await?;
+    Ok(HttpResponse::NoContent().finish())
 }
@@ -1,5 +1,10 @@
 pub mod product_dao;
+mod query;
+pub mod role_dao;
+pub mod totp_dao;
 pub mod user_dao;
 
 pub use product_dao::ProductDao;
+pub use role_dao::RoleDao;
+pub use totp_dao::TotpDao;
 pub use user_dao::UserDao;
@@ -1,10 +1,33 @@
-use crate::models::{CreateProductDto, Product, ProductQuery, UpdateProductDto};
+use crate::dao::query::{SelectBuilder, UpdateBuilder};
+use crate::models::pagination::{SortColumn, SqlType};
+use crate::models::{CreateProductDto, PageRequest, Product, ProductQuery, UpdateProductDto};
 use sqlx::{PgPool, postgres::PgQueryResult};
 use uuid::Uuid;
 
 pub struct ProductDao;
 
 impl ProductDao {
+    /// Columns product listings may be sorted by
+    pub const SORT_COLUMNS: &'static [SortColumn] = &[
+        SortColumn {
+            name: "created_at",
+            sql_type: SqlType::Timestamptz,
+        },
+        SortColumn {
+            name: "name",
+            sql_type: SqlType::Text,
+        },
+        SortColumn {
+            name: "price",
+            sql_type: SqlType::Numeric,
+        },
+        SortColumn {
+            name: "stock",
+            sql_type: SqlType::Integer,
+        },
+    ];
+
+    #[tracing::instrument(name = "ProductDao::create", skip_all, fields(db.system = "postgresql"))]
     pub async fn create(
         pool: &PgPool,
         dto: &CreateProductDto,
@@ -22,6 +45,7 @@ impl ProductDao {
         .await
     }
 
+    #[tracing::instrument(name = "ProductDao::find_by_id", skip_all, fields(db.system = "postgresql"))]
     pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Product, sqlx::Error> {
         sqlx::query_as::<_, Product>("SELECT * FROM rustack.products WHERE id = $1")
             .bind(id)
@@ -29,67 +53,56 @@ impl ProductDao {
             .await
     }
 
+    #[tracing::instrument(name = "ProductDao::find_all_dynamic", skip_all, fields(db.system = "postgresql"))]
     pub async fn find_all_dynamic(
         pool: &PgPool,
         query_params: &ProductQuery,
+        page: &PageRequest,
     ) -> Result<Vec<Product>, sqlx::Error> {
-        let mut query = String::from("SELECT * FROM rustack.products WHERE 1=1");
-
-        if let Some(name) = &query_params.name {
-            query.push_str(&format!(" AND name ILIKE '%{}%'", name));
-        }
-        if let Some(min_price) = query_params.min_price {
-            query.push_str(&format!(" AND price >= {}", min_price));
-        }
-        if let Some(max_price) = query_params.max_price {
-            query.push_str(&format!(" AND price <= {}", max_price));
-        }
-        if let Some(min_stock) = query_params.min_stock {
-            query.push_str(&format!(" AND stock >= {}", min_stock));
-        }
-
-        query.push_str(" ORDER BY created_at DESC");
-
-        sqlx::query_as::<_, Product>(&query).fetch_all(pool).await
+        let mut query = SelectBuilder::new("rustack.products");
+        query
+            .contains("name", query_params.name.as_deref())
+            .filter("price", ">=", query_params.min_price)
+            .filter("price", "<=", query_params.max_price)
+            .filter("stock", ">=", query_params.min_stock);
+
+        let mut query = query.paginate(page);
+        query.build_query_as::<Product>().fetch_all(pool).await
     }
 
+    /// Update a product; with `owner`, only if that user created it.
+    /// `None` when no row matched.
+    #[tracing::instrument(name = "ProductDao::update", skip_all, fields(db.system = "postgresql"))]
     pub async fn update(
         pool: &PgPool,
         id: Uuid,
         dto: &UpdateProductDto,
-    ) -> Result<Product, sqlx::Error> {
-        let mut query = String::from("UPDATE rustack.products SET updated_at = NOW()");
-        let mut params: Vec<String> = vec![];
-
-        if let Some(name) = &dto.name {
-            params.push(format!("name = '{}'", name));
-        }
-
-        if let Some(description) = &dto.description {
-            params.push(format!("description = '{}'", description));
-        }
-
-        if let Some(price) = dto.price {
-            params.push(format!("price = {}", price));
-        }
-
-        if let Some(stock) = dto.stock {
-            params.push(format!("stock = {}", stock));
-        }
-
-        if !params.is_empty() {
-            query.push_str(&format!(", {}", params.join(", ")));
-        }
-
-        query.push_str(&format!(" WHERE id = '{}' RETURNING *", id));
-
-        sqlx::query_as::<_, Product>(&query).fetch_one(pool).await
+        owner: Option<Uuid>,
+    ) -> Result<Option<Product>, sqlx::Error> {
+        let mut query = UpdateBuilder::new("rustack.products");
+        query
+            .set("name", dto.name.as_deref())
+            .set("description", dto.description.as_deref())
+            .set("price", dto.price)
+            .set("stock", dto.stock);
+
+        let mut query = query.where_id_and(id, "created_by", owner);
+        query.build_query_as::<Product>().fetch_optional(pool).await
     }
 
-    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<PgQueryResult, sqlx::Error> {
-        sqlx::query("DELETE FROM rustack.products WHERE id = $1")
-            .bind(id)
-            .execute(pool)
-            .await
+    /// Delete a product; with `owner`, only if that user created it
+    #[tracing::instrument(name = "ProductDao::delete", skip_all, fields(db.system = "postgresql"))]
+    pub async fn delete(
+        pool: &PgPool,
+        id: Uuid,
+        owner: Option<Uuid>,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query(
+            "DELETE FROM rustack.products WHERE id = $1 AND ($2::uuid IS NULL OR created_by = $2)",
+        )
+        .bind(id)
+        .bind(owner)
+        .execute(pool)
+        .await
     }
 }
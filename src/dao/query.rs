@@ -0,0 +1,227 @@
+//! Parameterized query builders shared by the DAOs.
+//!
+//! Table and column names are always `&'static str` supplied by the DAO itself.
+//! Every caller-supplied value goes through `push_bind`, so it travels to
+//! Postgres as a bound parameter and is never spliced into the SQL text.
+
+use sqlx::{Encode, Postgres, QueryBuilder, Type};
+use uuid::Uuid;
+
+use crate::models::pagination::{CursorValue, PageRequest};
+
+/// `SELECT * FROM table` with optional `AND` filters
+pub struct SelectBuilder<'args> {
+    builder: QueryBuilder<'args, Postgres>,
+}
+
+impl<'args> SelectBuilder<'args> {
+    pub fn new(table: &'static str) -> Self {
+        Self {
+            builder: QueryBuilder::new(format!("SELECT * FROM {} WHERE 1=1", table)),
+        }
+    }
+
+    /// Append `AND column op $n` when a value is present
+    pub fn filter<T>(
+        &mut self,
+        column: &'static str,
+        op: &'static str,
+        value: Option<T>,
+    ) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Type<Postgres>,
+    {
+        if let Some(value) = value {
+            self.builder.push(format_args!(" AND {} {} ", column, op));
+            self.builder.push_bind(value);
+        }
+        self
+    }
+
+    /// Case-insensitive substring match; `%`, `_` and `\` in the value match literally
+    pub fn contains(&mut self, column: &'static str, value: Option<&str>) -> &mut Self {
+        if let Some(value) = value {
+            self.builder.push(format_args!(" AND {} ILIKE ", column));
+            self.builder.push_bind(format!("%{}%", escape_like(value)));
+        }
+        self
+    }
+
+    /// Keyset pagination: rows after the cursor, ordered by the sort column then id.
+    ///
+    /// Fetches `limit + 1` rows so the caller can tell whether another page exists.
+    pub fn paginate(mut self, page: &PageRequest) -> QueryBuilder<'args, Postgres> {
+        let column = page.sort.column.name;
+        let (comparison, direction) = if page.sort.descending {
+            ("<", "DESC")
+        } else {
+            (">", "ASC")
+        };
+
+        if let Some((value, id)) = &page.after {
+            self.builder
+                .push(format_args!(" AND ({}, id) {} (", column, comparison));
+            match value {
+                CursorValue::Timestamptz(value) => self.builder.push_bind(*value),
+                CursorValue::Text(value) => self.builder.push_bind(value.clone()),
+                CursorValue::Numeric(value) => self.builder.push_bind(*value),
+                CursorValue::Integer(value) => self.builder.push_bind(*value),
+            };
+            self.builder.push(", ");
+            self.builder.push_bind(*id);
+            self.builder.push(")");
+        }
+
+        self.builder.push(format_args!(
+            " ORDER BY {} {}, id {} LIMIT ",
+            column, direction, direction
+        ));
+        self.builder.push_bind(page.limit + 1);
+        self.builder
+    }
+}
+
+/// `UPDATE table SET updated_at = NOW()` plus only the columns that were provided
+pub struct UpdateBuilder<'args> {
+    builder: QueryBuilder<'args, Postgres>,
+}
+
+impl<'args> UpdateBuilder<'args> {
+    pub fn new(table: &'static str) -> Self {
+        Self {
+            builder: QueryBuilder::new(format!("UPDATE {} SET updated_at = NOW()", table)),
+        }
+    }
+
+    /// Append `, column = $n` when a value is present
+    pub fn set<T>(&mut self, column: &'static str, value: Option<T>) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Type<Postgres>,
+    {
+        if let Some(value) = value {
+            self.builder.push(format_args!(", {} = ", column));
+            self.builder.push_bind(value);
+        }
+        self
+    }
+
+    /// Restrict the update to one row and return it
+    pub fn where_id(mut self, id: Uuid) -> QueryBuilder<'args, Postgres> {
+        self.builder
+            .push(" WHERE id = ")
+            .push_bind(id)
+            .push(" RETURNING *");
+        self.builder
+    }
+
+    /// Like `where_id`, also requiring `column = value` when a value is present,
+    /// so a condition checked by the caller holds at the moment of the write
+    pub fn where_id_and<T>(
+        mut self,
+        id: Uuid,
+        column: &'static str,
+        value: Option<T>,
+    ) -> QueryBuilder<'args, Postgres>
+    where
+        T: 'args + Encode<'args, Postgres> + Type<Postgres>,
+    {
+        self.builder.push(" WHERE id = ").push_bind(id);
+        if let Some(value) = value {
+            self.builder.push(format_args!(" AND {} = ", column));
+            self.builder.push_bind(value);
+        }
+        self.builder.push(" RETURNING *");
+        self.builder
+    }
+}
+
+/// Escape LIKE wildcards so user input is matched literally
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::pagination::{SortColumn, SortOrder, SqlType};
+
+    const HOSTILE: &str = "x' OR 1=1; DROP TABLE rustack.users; --";
+
+    static NAME: SortColumn = SortColumn {
+        name: "name",
+        sql_type: SqlType::Text,
+    };
+
+    #[test]
+    fn escape_like_escapes_wildcards_and_backslash() {
+        assert_eq!(escape_like("50%_off\\"), "50\\%\\_off\\\\");
+        assert_eq!(escape_like("plain"), "plain");
+    }
+
+    #[test]
+    fn select_binds_every_value() {
+        let mut select = SelectBuilder::new("rustack.products");
+        select
+            .filter("price", ">=", Some(HOSTILE.to_string()))
+            .filter::<i32>("stock", ">=", None)
+            .contains("name", Some(HOSTILE));
+
+        assert_eq!(
+            select.builder.sql(),
+            "SELECT * FROM rustack.products WHERE 1=1 AND price >= $1 AND name ILIKE $2"
+        );
+    }
+
+    #[test]
+    fn paginate_binds_cursor_and_limit() {
+        let page = PageRequest {
+            limit: 20,
+            sort: SortOrder {
+                column: &NAME,
+                descending: true,
+            },
+            after: Some((CursorValue::Text(HOSTILE.to_string()), Uuid::nil())),
+        };
+        let query = SelectBuilder::new("rustack.products").paginate(&page);
+
+        assert_eq!(
+            query.sql(),
+            "SELECT * FROM rustack.products WHERE 1=1 AND (name, id) < ($1, $2) \
+             ORDER BY name DESC, id DESC LIMIT $3"
+        );
+    }
+
+    #[test]
+    fn update_binds_every_value() {
+        let mut update = UpdateBuilder::new("rustack.users");
+        update
+            .set("username", Some(HOSTILE))
+            .set::<&str>("email", None);
+        let query = update.where_id(Uuid::nil());
+
+        assert_eq!(
+            query.sql(),
+            "UPDATE rustack.users SET updated_at = NOW(), username = $1 WHERE id = $2 RETURNING *"
+        );
+    }
+
+    #[test]
+    fn update_binds_the_extra_condition() {
+        let mut update = UpdateBuilder::new("rustack.products");
+        update.set("stock", Some(5));
+        let query = update.where_id_and(Uuid::nil(), "created_by", Some(Uuid::nil()));
+
+        assert_eq!(
+            query.sql(),
+            "UPDATE rustack.products SET updated_at = NOW(), stock = $1 \
+             WHERE id = $2 AND created_by = $3 RETURNING *"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use crate::models::Role;
+use sqlx::{PgPool, postgres::PgQueryResult};
+use uuid::Uuid;
+
+pub struct RoleDao;
+
+impl RoleDao {
+    #[tracing::instrument(name = "RoleDao::find_all", skip_all, fields(db.system = "postgresql"))]
+    pub async fn find_all(pool: &PgPool) -> Result<Vec<Role>, sqlx::Error> {
+        sqlx::query_as::<_, Role>("SELECT * FROM rustack.roles ORDER BY name")
+            .fetch_all(pool)
+            .await
+    }
+
+    #[tracing::instrument(name = "RoleDao::find_by_name", skip_all, fields(db.system = "postgresql"))]
+    pub async fn find_by_name(pool: &PgPool, name: &str) -> Result<Role, sqlx::Error> {
+        sqlx::query_as::<_, Role>("SELECT * FROM rustack.roles WHERE name = $1")
+            .bind(name)
+            .fetch_one(pool)
+            .await
+    }
+
+    #[tracing::instrument(name = "RoleDao::find_by_user", skip_all, fields(db.system = "postgresql"))]
+    pub async fn find_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<Role>, sqlx::Error> {
+        sqlx::query_as::<_, Role>(
+            "SELECT r.* FROM rustack.roles r JOIN rustack.user_roles ur ON ur.role_id = r.id WHERE ur.user_id = $1 ORDER BY r.name",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    #[tracing::instrument(name = "RoleDao::find_permissions_by_user", skip_all, fields(db.system = "postgresql"))]
+    pub async fn find_permissions_by_user(
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT DISTINCT p.name FROM rustack.permissions p JOIN rustack.role_permissions rp ON rp.permission_id = p.id JOIN rustack.user_roles ur ON ur.role_id = rp.role_id WHERE ur.user_id = $1 ORDER BY p.name",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    #[tracing::instrument(name = "RoleDao::assign", skip_all, fields(db.system = "postgresql"))]
+    pub async fn assign(
+        pool: &PgPool,
+        user_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO rustack.user_roles (user_id, role_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(user_id)
+        .bind(role_id)
+        .execute(pool)
+        .await
+    }
+
+    #[tracing::instrument(name = "RoleDao::revoke", skip_all, fields(db.system = "postgresql"))]
+    pub async fn revoke(
+        pool: &PgPool,
+        user_id: Uuid,
+        role_id: Uuid,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query("DELETE FROM rustack.user_roles WHERE user_id = $1 AND role_id = $2")
+            .bind(user_id)
+            .bind(role_id)
+            .execute(pool)
+            .await
+    }
+}
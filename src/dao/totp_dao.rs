@@ -0,0 +1,140 @@
+use crate::models::UserTotp;
+use sqlx::{PgPool, postgres::PgQueryResult};
+use uuid::Uuid;
+
+pub struct TotpDao;
+
+impl TotpDao {
+    #[tracing::instrument(name = "TotpDao::find", skip_all, fields(db.system = "postgresql"))]
+    pub async fn find(pool: &PgPool, user_id: Uuid) -> Result<Option<UserTotp>, sqlx::Error> {
+        sqlx::query_as::<_, UserTotp>(
+            "SELECT user_id, secret, enabled_at FROM rustack.user_totp WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    #[tracing::instrument(name = "TotpDao::is_enabled", skip_all, fields(db.system = "postgresql"))]
+    pub async fn is_enabled(pool: &PgPool, user_id: Uuid) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM rustack.user_totp WHERE user_id = $1 AND enabled_at IS NOT NULL)",
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Store a new unconfirmed secret, replacing any earlier unconfirmed one.
+    /// Affects no rows when 2FA is already enabled.
+    #[tracing::instrument(name = "TotpDao::upsert_pending", skip_all, fields(db.system = "postgresql"))]
+    pub async fn upsert_pending(
+        pool: &PgPool,
+        user_id: Uuid,
+        secret: &[u8],
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO rustack.user_totp (user_id, secret) VALUES ($1, $2) \
+             ON CONFLICT (user_id) DO UPDATE SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = NOW() \
+             WHERE rustack.user_totp.enabled_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(secret)
+        .execute(pool)
+        .await
+    }
+
+    /// Confirm the enrollment and replace the recovery codes in one transaction.
+    /// Affects no rows, and leaves the recovery codes alone, when 2FA is already enabled.
+    #[tracing::instrument(name = "TotpDao::enable", skip_all, fields(db.system = "postgresql"))]
+    pub async fn enable(
+        pool: &PgPool,
+        user_id: Uuid,
+        step: i64,
+        recovery_code_hashes: &[String],
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE rustack.user_totp SET enabled_at = NOW(), last_used_step = $2 \
+             WHERE user_id = $1 AND enabled_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(step)
+        .execute(&mut *tx)
+        .await?;
+        // Already confirmed by a concurrent request: keep the codes it issued
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(result);
+        }
+
+        sqlx::query("DELETE FROM rustack.user_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "INSERT INTO rustack.user_recovery_codes (user_id, code_hash) SELECT $1, UNNEST($2::text[])",
+        )
+        .bind(user_id)
+        .bind(recovery_code_hashes)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result)
+    }
+
+    /// Accept `step` only if it is newer than every step used before, so each
+    /// code works once. Affects no rows for a replayed code.
+    #[tracing::instrument(name = "TotpDao::record_step", skip_all, fields(db.system = "postgresql"))]
+    pub async fn record_step(
+        pool: &PgPool,
+        user_id: Uuid,
+        step: i64,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query(
+            "UPDATE rustack.user_totp SET last_used_step = $2 \
+             WHERE user_id = $1 AND enabled_at IS NOT NULL AND (last_used_step IS NULL OR last_used_step < $2)",
+        )
+        .bind(user_id)
+        .bind(step)
+        .execute(pool)
+        .await
+    }
+
+    /// Mark an unused recovery code as used. Affects no rows for an unknown or used code.
+    #[tracing::instrument(name = "TotpDao::use_recovery_code", skip_all, fields(db.system = "postgresql"))]
+    pub async fn use_recovery_code(
+        pool: &PgPool,
+        user_id: Uuid,
+        code_hash: &str,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query(
+            "UPDATE rustack.user_recovery_codes SET used_at = NOW() \
+             WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(code_hash)
+        .execute(pool)
+        .await
+    }
+
+    /// Remove the secret and every recovery code
+    #[tracing::instrument(name = "TotpDao::delete", skip_all, fields(db.system = "postgresql"))]
+    pub async fn delete(pool: &PgPool, user_id: Uuid) -> Result<PgQueryResult, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM rustack.user_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM rustack.user_totp WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result)
+    }
+}
@@ -1,25 +1,58 @@
-use crate::models::{CreateUserDto, UpdateUserDto, User};
+use crate::dao::query::{SelectBuilder, UpdateBuilder};
+use crate::models::pagination::{SortColumn, SqlType};
+use crate::models::{CreateUserDto, PageRequest, UpdateUserDto, User};
+use chrono::{DateTime, Utc};
 use sqlx::{PgPool, postgres::PgQueryResult};
 use uuid::Uuid;
 
 pub struct UserDao;
 
 impl UserDao {
+    /// Columns user listings may be sorted by
+    pub const SORT_COLUMNS: &'static [SortColumn] = &[
+        SortColumn {
+            name: "created_at",
+            sql_type: SqlType::Timestamptz,
+        },
+        SortColumn {
+            name: "username",
+            sql_type: SqlType::Text,
+        },
+        SortColumn {
+            name: "email",
+            sql_type: SqlType::Text,
+        },
+    ];
+
+    /// Insert the user and grant `role_id` in one transaction, so no user is left without a role
+    #[tracing::instrument(name = "UserDao::create", skip_all, fields(db.system = "postgresql"))]
     pub async fn create(
         pool: &PgPool,
         dto: &CreateUserDto,
         password_hash: &str,
+        role_id: Uuid,
     ) -> Result<User, sqlx::Error> {
-        sqlx::query_as::<_, User>(
+        let mut tx = pool.begin().await?;
+
+        let user = sqlx::query_as::<_, User>(
             "INSERT INTO rustack.users (username, email, password_hash) VALUES ($1, $2, $3) RETURNING *",
         )
         .bind(&dto.username)
         .bind(&dto.email)
         .bind(password_hash)
-        .fetch_one(pool)
-        .await
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query("INSERT INTO rustack.user_roles (user_id, role_id) VALUES ($1, $2)")
+            .bind(user.id)
+            .bind(role_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(user)
     }
 
+    #[tracing::instrument(name = "UserDao::find_by_id", skip_all, fields(db.system = "postgresql"))]
     pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<User, sqlx::Error> {
         sqlx::query_as::<_, User>("SELECT * FROM rustack.users WHERE id = $1")
             .bind(id)
@@ -27,6 +60,7 @@ impl UserDao {
             .await
     }
 
+    #[tracing::instrument(name = "UserDao::find_by_username", skip_all, fields(db.system = "postgresql"))]
     pub async fn find_by_username(pool: &PgPool, username: &str) -> Result<User, sqlx::Error> {
         sqlx::query_as::<_, User>("SELECT * FROM rustack.users WHERE username = $1")
             .bind(username)
@@ -34,42 +68,74 @@ impl UserDao {
             .await
     }
 
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<User>, sqlx::Error> {
-        sqlx::query_as::<_, User>("SELECT * FROM rustack.users ORDER BY created_at DESC")
-            .fetch_all(pool)
+    #[tracing::instrument(name = "UserDao::find_by_email", skip_all, fields(db.system = "postgresql"))]
+    pub async fn find_by_email(pool: &PgPool, email: &str) -> Result<Option<User>, sqlx::Error> {
+        sqlx::query_as::<_, User>("SELECT * FROM rustack.users WHERE email = $1")
+            .bind(email)
+            .fetch_optional(pool)
             .await
     }
 
+    #[tracing::instrument(name = "UserDao::find_all", skip_all, fields(db.system = "postgresql"))]
+    pub async fn find_all(pool: &PgPool, page: &PageRequest) -> Result<Vec<User>, sqlx::Error> {
+        let mut query = SelectBuilder::new("rustack.users").paginate(page);
+        query.build_query_as::<User>().fetch_all(pool).await
+    }
+
+    #[tracing::instrument(name = "UserDao::update", skip_all, fields(db.system = "postgresql"))]
     pub async fn update(
         pool: &PgPool,
         id: Uuid,
         dto: &UpdateUserDto,
         password_hash: Option<&str>,
     ) -> Result<User, sqlx::Error> {
-        let mut query = String::from("UPDATE rustack.users SET updated_at = NOW()");
-        let mut params: Vec<String> = vec![];
+        let mut query = UpdateBuilder::new("rustack.users");
+        query
+            .set("username", dto.username.as_deref())
+            .set("email", dto.email.as_deref())
+            // A new email has to be verified again
+            .set(
+                "email_verified_at",
+                dto.email.as_ref().map(|_| None::<DateTime<Utc>>),
+            )
+            .set("password_hash", password_hash);
 
-        if let Some(username) = &dto.username {
-            params.push(format!("username = '{}'", username));
-        }
-
-        if let Some(email) = &dto.email {
-            params.push(format!("email = '{}'", email));
-        }
-
-        if let Some(hash) = password_hash {
-            params.push(format!("password_hash = '{}'", hash));
-        }
-
-        if !params.is_empty() {
-            query.push_str(&format!(", {}", params.join(", ")));
-        }
+        let mut query = query.where_id(id);
+        query.build_query_as::<User>().fetch_one(pool).await
+    }
 
-        query.push_str(&format!(" WHERE id = '{}' RETURNING *", id));
+    #[tracing::instrument(name = "UserDao::update_password", skip_all, fields(db.system = "postgresql"))]
+    pub async fn update_password(
+        pool: &PgPool,
+        id: Uuid,
+        password_hash: &str,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query("UPDATE rustack.users SET password_hash = $2, updated_at = NOW() WHERE id = $1")
+            .bind(id)
+            .bind(password_hash)
+            .execute(pool)
+            .await
+    }
 
-        sqlx::query_as::<_, User>(&query).fetch_one(pool).await
+    /// Mark `email` verified, but only while it is still the user's email.
+    /// Affects no rows if it changed after the link was sent.
+    #[tracing::instrument(name = "UserDao::mark_email_verified", skip_all, fields(db.system = "postgresql"))]
+    pub async fn mark_email_verified(
+        pool: &PgPool,
+        id: Uuid,
+        email: &str,
+    ) -> Result<PgQueryResult, sqlx::Error> {
+        sqlx::query(
+            "UPDATE rustack.users SET email_verified_at = COALESCE(email_verified_at, NOW()), updated_at = NOW() \
+             WHERE id = $1 AND email = $2",
+        )
+        .bind(id)
+        .bind(email)
+        .execute(pool)
+        .await
     }
 
+    #[tracing::instrument(name = "UserDao::delete", skip_all, fields(db.system = "postgresql"))]
     pub async fn delete(pool: &PgPool, id: Uuid) -> Result<PgQueryResult, sqlx::Error> {
         sqlx::query("DELETE FROM rustack.users WHERE id = $1")
             .bind(id)
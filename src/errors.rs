@@ -0,0 +1,197 @@
+use actix_web::http::StatusCode;
+use actix_web::http::header::{ContentType, RETRY_AFTER};
+use actix_web::{HttpRequest, HttpResponse, ResponseError, error};
+use serde::Serialize;
+use std::fmt;
+use utoipa::ToSchema;
+
+use crate::middleware::current_request_id;
+use crate::validation::FieldError;
+
+/// Crate-wide error type rendered as an RFC 7807 `application/problem+json` body
+#[derive(Debug)]
+pub enum AppError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    Validation(Vec<FieldError>),
+    /// Detail, and seconds until a retry may succeed when known (sent as `Retry-After`)
+    TooManyRequests(String, Option<u64>),
+    ServiceUnavailable(String),
+    Internal(String),
+}
+
+/// RFC 7807 problem details
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(example = json!({
+    "type": "/problems/not-found",
+    "title": "Not Found",
+    "status": 404,
+    "detail": "User not found",
+    "request_id": "0f8fad5b-d9cb-469f-a165-70867728950e"
+}))]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+impl AppError {
+    /// Map `RowNotFound` to a resource-specific 404, any other error as usual
+    pub fn not_found_or(err: sqlx::Error, message: &str) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => AppError::NotFound(message.to_string()),
+            e => e.into(),
+        }
+    }
+
+    /// Short slug identifying the problem type
+    fn slug(&self) -> &'static str {
+        match self {
+            AppError::BadRequest(_) => "bad-request",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::NotFound(_) => "not-found",
+            AppError::Conflict(_) => "conflict",
+            AppError::Validation(_) => "validation-failed",
+            AppError::TooManyRequests(..) => "too-many-requests",
+            AppError::ServiceUnavailable(_) => "service-unavailable",
+            AppError::Internal(_) => "internal-error",
+        }
+    }
+
+    fn detail(&self) -> &str {
+        match self {
+            AppError::BadRequest(detail)
+            | AppError::Unauthorized(detail)
+            | AppError::Forbidden(detail)
+            | AppError::NotFound(detail)
+            | AppError::Conflict(detail)
+            | AppError::TooManyRequests(detail, _)
+            | AppError::ServiceUnavailable(detail)
+            | AppError::Internal(detail) => detail,
+            AppError::Validation(_) => "Request validation failed",
+        }
+    }
+
+    pub fn problem(&self) -> ProblemDetails {
+        let status = self.status_code();
+
+        // Never leak internal error messages to clients; they are logged instead
+        let detail = match self {
+            AppError::Internal(_) => "An unexpected error occurred".to_string(),
+            _ => self.detail().to_string(),
+        };
+
+        ProblemDetails {
+            problem_type: format!("/problems/{}", self.slug()),
+            title: status.canonical_reason().unwrap_or("Error").to_string(),
+            status: status.as_u16(),
+            detail,
+            request_id: current_request_id(),
+            errors: match self {
+                AppError::Validation(errors) => errors.clone(),
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.detail())
+    }
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::TooManyRequests(..) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if self.status_code().is_server_error() {
+            tracing::error!(error = %self, "Request failed");
+        }
+
+        let mut res = HttpResponse::build(self.status_code());
+        if let AppError::TooManyRequests(_, Some(retry_after)) = self {
+            res.insert_header((RETRY_AFTER, *retry_after));
+        }
+        res.content_type(ContentType(
+            "application/problem+json".parse().expect("valid mime type"),
+        ))
+        .json(self.problem())
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        match &err {
+            sqlx::Error::RowNotFound => AppError::NotFound("Resource not found".to_string()),
+            sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => {
+                AppError::ServiceUnavailable("Database is unavailable".to_string())
+            }
+            sqlx::Error::Database(db_err) => {
+                let constraint = db_err.constraint().unwrap_or("unknown");
+                match db_err.code().as_deref() {
+                    // unique_violation
+                    Some("23505") => AppError::Conflict(format!(
+                        "A resource with the same unique value already exists ({})",
+                        constraint
+                    )),
+                    // foreign_key_violation
+                    Some("23503") => AppError::Conflict(format!(
+                        "Referenced resource does not exist or is still in use ({})",
+                        constraint
+                    )),
+                    // check_violation, not_null_violation, string_data_right_truncation
+                    Some("23514") | Some("23502") | Some("22001") => {
+                        AppError::BadRequest(format!("Invalid value: {}", db_err.message()))
+                    }
+                    _ => AppError::Internal(format!("Database error: {}", err)),
+                }
+            }
+            _ => AppError::Internal(format!("Database error: {}", err)),
+        }
+    }
+}
+
+impl From<redis::RedisError> for AppError {
+    fn from(err: redis::RedisError) -> Self {
+        tracing::error!(error = %err, "Redis error");
+        AppError::ServiceUnavailable("Session store is unavailable".to_string())
+    }
+}
+
+/// Render JSON body extraction failures as problem+json
+pub fn json_error_handler(err: error::JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    AppError::BadRequest(err.to_string()).into()
+}
+
+/// Render path extraction failures (e.g. malformed UUIDs) as problem+json
+pub fn path_error_handler(err: error::PathError, _req: &HttpRequest) -> actix_web::Error {
+    AppError::BadRequest(err.to_string()).into()
+}
+
+/// Render query string extraction failures as problem+json
+pub fn query_error_handler(err: error::QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    AppError::BadRequest(err.to_string()).into()
+}
@@ -0,0 +1,238 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::errors::{Error as JwtError, ErrorKind};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+    OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    ThumbprintHash,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, crypto};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use simple_asn1::ASN1Block;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::configs::auth::{AuthConfig, JwtAlgorithm};
+
+/// Keys for signing and verifying access tokens, loaded once at startup.
+///
+/// RS256 and EdDSA tokens carry a `kid` header, the RFC 7638 thumbprint of the
+/// public key, which selects the verification key. Every public key is
+/// published as a JWKS so other services can verify tokens without a shared
+/// secret. HS256 tokens have no `kid` and are checked against `jwt_secret`.
+pub struct JwtKeys {
+    algorithm: Algorithm,
+    kid: Option<String>,
+    signing_key: EncodingKey,
+    verification_keys: HashMap<String, (Algorithm, DecodingKey)>,
+    hs256_key: Option<DecodingKey>,
+    jwks: JwkSet,
+}
+
+#[derive(Debug)]
+pub struct JwtKeyError(pub String);
+
+impl fmt::Display for JwtKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for JwtKeyError {}
+
+/// A public key read from a PEM file, with its JWK
+struct PublicKey {
+    algorithm: Algorithm,
+    kid: String,
+    key: DecodingKey,
+    jwk: Jwk,
+}
+
+impl JwtKeys {
+    /// Read the key files named by `config`
+    pub fn from_config(config: &AuthConfig) -> Result<Self, JwtKeyError> {
+        let hs256_key = config
+            .uses_hs256()
+            .then(|| DecodingKey::from_secret(config.jwt_secret.as_bytes()));
+
+        let mut keys = match config.jwt_algorithm {
+            JwtAlgorithm::HS256 => Self {
+                algorithm: Algorithm::HS256,
+                kid: None,
+                signing_key: EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+                verification_keys: HashMap::new(),
+                hs256_key,
+                jwks: JwkSet { keys: Vec::new() },
+            },
+            JwtAlgorithm::RS256 | JwtAlgorithm::EdDSA => {
+                let (private_path, public_path) = config
+                    .jwt_private_key_file
+                    .as_deref()
+                    .zip(config.jwt_public_key_file.as_deref())
+                    .ok_or_else(|| {
+                        JwtKeyError(format!(
+                            "{} needs auth.jwt_private_key_file and auth.jwt_public_key_file",
+                            config.jwt_algorithm
+                        ))
+                    })?;
+
+                let pem = read_pem(private_path)?;
+                let signing_key = match config.jwt_algorithm {
+                    JwtAlgorithm::RS256 => EncodingKey::from_rsa_pem(&pem),
+                    _ => EncodingKey::from_ed_pem(&pem),
+                }
+                .map_err(|e| {
+                    JwtKeyError(format!(
+                        "{} is not a {} private key: {}",
+                        private_path, config.jwt_algorithm, e
+                    ))
+                })?;
+
+                let public = read_public_key(public_path)?;
+                if !is_pair(&signing_key, &public) {
+                    return Err(JwtKeyError(format!(
+                        "{} is not the public key of {}",
+                        public_path, private_path
+                    )));
+                }
+
+                let mut keys = Self {
+                    algorithm: public.algorithm,
+                    kid: Some(public.kid.clone()),
+                    signing_key,
+                    verification_keys: HashMap::new(),
+                    hs256_key,
+                    jwks: JwkSet { keys: Vec::new() },
+                };
+                keys.add(public);
+                keys
+            }
+        };
+
+        for path in &config.jwt_verification_key_files {
+            keys.add(read_public_key(path)?);
+        }
+
+        Ok(keys)
+    }
+
+    /// `kid` of the key new tokens are signed with; `None` for HS256
+    pub fn current_kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    /// Public keys tokens may be signed with, as a JWK Set
+    pub fn jwks(&self) -> &JwkSet {
+        &self.jwks
+    }
+
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, JwtError> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = self.kid.clone();
+        jsonwebtoken::encode(&header, claims, &self.signing_key)
+    }
+
+    /// Verify the signature with the key named by the token's `kid` and check expiry
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<T, JwtError> {
+        let header = jsonwebtoken::decode_header(token)?;
+        let (algorithm, key) = match &header.kid {
+            Some(kid) => self
+                .verification_keys
+                .get(kid)
+                .map(|(algorithm, key)| (*algorithm, key)),
+            None => self.hs256_key.as_ref().map(|key| (Algorithm::HS256, key)),
+        }
+        .ok_or(ErrorKind::InvalidToken)?;
+
+        // Pinning the algorithm to the key rules out algorithm confusion
+        let token_data = jsonwebtoken::decode::<T>(token, key, &Validation::new(algorithm))?;
+        Ok(token_data.claims)
+    }
+
+    fn add(&mut self, public: PublicKey) {
+        // Listing the signing key again, e.g. mid-rotation, is harmless
+        if self.verification_keys.contains_key(&public.kid) {
+            return;
+        }
+        self.verification_keys
+            .insert(public.kid, (public.algorithm, public.key));
+        self.jwks.keys.push(public.jwk);
+    }
+}
+
+fn read_pem(path: &str) -> Result<Vec<u8>, JwtKeyError> {
+    std::fs::read(path).map_err(|e| JwtKeyError(format!("cannot read {}: {}", path, e)))
+}
+
+/// RSA or Ed25519 public key; the algorithm follows from the key type
+fn read_public_key(path: &str) -> Result<PublicKey, JwtKeyError> {
+    let pem = read_pem(path)?;
+
+    let (algorithm, key, parameters) = if let Ok(key) = DecodingKey::from_rsa_pem(&pem) {
+        let (n, e) = rsa_components(key.as_bytes())
+            .ok_or_else(|| JwtKeyError(format!("{} is not an RSA public key", path)))?;
+        let parameters = AlgorithmParameters::RSA(RSAKeyParameters {
+            key_type: RSAKeyType::RSA,
+            n: URL_SAFE_NO_PAD.encode(n),
+            e: URL_SAFE_NO_PAD.encode(e),
+        });
+        (Algorithm::RS256, key, parameters)
+    } else if let Ok(key) = DecodingKey::from_ed_pem(&pem) {
+        let parameters = AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x: URL_SAFE_NO_PAD.encode(key.as_bytes()),
+        });
+        (Algorithm::EdDSA, key, parameters)
+    } else {
+        return Err(JwtKeyError(format!(
+            "{} is not an RSA or Ed25519 public key in PEM format",
+            path
+        )));
+    };
+
+    let mut jwk = Jwk {
+        common: CommonParameters::default(),
+        algorithm: parameters,
+    };
+    let kid = jwk.thumbprint(ThumbprintHash::SHA256);
+    jwk.common = CommonParameters {
+        public_key_use: Some(PublicKeyUse::Signature),
+        key_algorithm: Some(match algorithm {
+            Algorithm::RS256 => KeyAlgorithm::RS256,
+            _ => KeyAlgorithm::EdDSA,
+        }),
+        key_id: Some(kid.clone()),
+        ..Default::default()
+    };
+
+    Ok(PublicKey {
+        algorithm,
+        kid,
+        key,
+        jwk,
+    })
+}
+
+/// Modulus and exponent of a PKCS#1 `RSAPublicKey`
+fn rsa_components(der: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    let blocks = simple_asn1::from_der(der).ok()?;
+    match blocks.as_slice() {
+        [ASN1Block::Sequence(_, fields)] => match fields.as_slice() {
+            [ASN1Block::Integer(_, n), ASN1Block::Integer(_, e)] => {
+                Some((n.to_bytes_be().1, e.to_bytes_be().1))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether `public` verifies what `private` signs
+fn is_pair(private: &EncodingKey, public: &PublicKey) -> bool {
+    let message = b"jwt key pair check";
+    crypto::sign(message, private, public.algorithm)
+        .and_then(|signature| crypto::verify(&signature, message, &public.key, public.algorithm))
+        .unwrap_or(false)
+}
@@ -0,0 +1,45 @@
+use futures::future::BoxFuture;
+use std::path::PathBuf;
+
+use super::{Email, MailError, Mailer};
+use crate::configs::mail::MailConfig;
+
+/// Writes each message to `outbox_dir` as an `.eml` file instead of sending it.
+/// For development and tests; the path is logged so links can be followed.
+pub struct FileMailer {
+    from: String,
+    outbox_dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(config: &MailConfig) -> Self {
+        Self {
+            from: config.from.clone(),
+            outbox_dir: PathBuf::from(&config.outbox_dir),
+        }
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send(&self, email: Email) -> BoxFuture<'_, Result<(), MailError>> {
+        Box::pin(async move {
+            tokio::fs::create_dir_all(&self.outbox_dir).await?;
+
+            let name = format!(
+                "{}-{}.eml",
+                chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+                uuid::Uuid::new_v4().simple()
+            );
+            let path = self.outbox_dir.join(name);
+            tokio::fs::write(&path, email.to_message(&self.from)).await?;
+
+            tracing::info!(
+                to = %email.to,
+                subject = %email.subject,
+                path = %path.display(),
+                "Email written to outbox"
+            );
+            Ok(())
+        })
+    }
+}
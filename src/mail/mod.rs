@@ -0,0 +1,80 @@
+use chrono::Utc;
+use futures::future::BoxFuture;
+use std::fmt;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::configs::mail::{MailConfig, MailTransport};
+
+mod file;
+mod smtp;
+
+pub use file::FileMailer;
+pub use smtp::SmtpMailer;
+
+/// A plain-text message to one recipient
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug)]
+pub struct MailError(pub String);
+
+impl fmt::Display for MailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for MailError {}
+
+impl From<std::io::Error> for MailError {
+    fn from(e: std::io::Error) -> Self {
+        MailError(e.to_string())
+    }
+}
+
+/// Delivers outgoing mail. Implementations are selected by `mail.transport`.
+pub trait Mailer: Send + Sync {
+    fn send(&self, email: Email) -> BoxFuture<'_, Result<(), MailError>>;
+}
+
+/// Build the mailer configured by `config`
+pub fn from_config(config: &MailConfig) -> Result<Arc<dyn Mailer>, MailError> {
+    Ok(match config.transport {
+        MailTransport::File => Arc::new(FileMailer::new(config)),
+        MailTransport::Smtp => Arc::new(SmtpMailer::new(config)?),
+    })
+}
+
+impl Email {
+    /// RFC 5322 message with CRLF line endings
+    pub fn to_message(&self, from: &str) -> String {
+        let domain = address(from).rsplit('@').next().unwrap_or("localhost");
+        let body = self.body.replace("\r\n", "\n").replace('\n', "\r\n");
+
+        format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMessage-ID: <{}@{}>\r\n\
+             MIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\n\
+             Content-Transfer-Encoding: 8bit\r\n\r\n{}\r\n",
+            from,
+            self.to,
+            self.subject,
+            Utc::now().to_rfc2822(),
+            Uuid::new_v4(),
+            domain,
+            body
+        )
+    }
+}
+
+/// The bare address of `Name <user@host>`, or the input when it has no angle brackets
+pub fn address(mailbox: &str) -> &str {
+    match (mailbox.find('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+        _ => mailbox.trim(),
+    }
+}
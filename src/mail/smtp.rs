@@ -0,0 +1,89 @@
+use futures::future::BoxFuture;
+use lettre::address::{Address, Envelope};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use std::time::Duration;
+
+use super::{Email, MailError, Mailer, address};
+use crate::configs::mail::{MailConfig, SmtpSecurity};
+
+/// Delivers mail through an SMTP relay, one message per connection.
+///
+/// `mail.smtp_security` selects plaintext, STARTTLS or implicit TLS; the
+/// production check requires one of the TLS modes for anything but a local relay.
+pub struct SmtpMailer {
+    from: String,
+    host: String,
+    port: u16,
+    timeout: Duration,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &MailConfig) -> Result<Self, MailError> {
+        let host = config.smtp_host.as_str();
+        let mut builder = match config.smtp_security {
+            SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+                .map_err(|e| MailError(format!("SMTP STARTTLS setup failed: {}", e)))?,
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+                .map_err(|e| MailError(format!("SMTP TLS setup failed: {}", e)))?,
+        }
+        .port(config.smtp_port)
+        .timeout(Some(config.timeout()));
+
+        if let Some((username, password)) = config
+            .smtp_username
+            .clone()
+            .zip(config.smtp_password.clone())
+        {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            from: config.from.clone(),
+            host: config.smtp_host.clone(),
+            port: config.smtp_port,
+            timeout: config.timeout(),
+            transport: builder.build(),
+        })
+    }
+
+    async fn deliver(&self, email: &Email) -> Result<(), MailError> {
+        let envelope = Envelope::new(
+            Some(parse_address(&self.from)?),
+            vec![parse_address(&email.to)?],
+        )
+        .map_err(|e| MailError(e.to_string()))?;
+
+        self.transport
+            .send_raw(&envelope, email.to_message(&self.from).as_bytes())
+            .await
+            .map_err(|e| MailError(format!("SMTP delivery failed: {}", e)))?;
+        Ok(())
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send(&self, email: Email) -> BoxFuture<'_, Result<(), MailError>> {
+        Box::pin(async move {
+            tokio::time::timeout(self.timeout, self.deliver(&email))
+                .await
+                .map_err(|_| {
+                    MailError(format!(
+                        "SMTP delivery to {}:{} timed out",
+                        self.host, self.port
+                    ))
+                })??;
+
+            tracing::info!(to = %email.to, subject = %email.subject, "Email sent via SMTP");
+            Ok(())
+        })
+    }
+}
+
+fn parse_address(mailbox: &str) -> Result<Address, MailError> {
+    address(mailbox)
+        .parse()
+        .map_err(|e| MailError(format!("invalid address {:?}: {}", mailbox, e)))
+}
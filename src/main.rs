@@ -1,31 +1,82 @@
 use actix_web::middleware::Logger;
-use actix_web::{App, HttpServer};
+use actix_web::{App, HttpServer, web};
+use clap::Parser;
 use dotenv::dotenv;
 
+mod cli;
 mod configs;
 mod controllers;
 mod dao;
+mod errors;
+mod jwt;
+mod mail;
+mod metrics;
 mod middleware;
 mod models;
 mod routes;
 mod services;
+mod shutdown;
+mod validation;
 
-use configs::{AppState, logging::LoggingConfig};
-use middleware::{CorsMiddleware, LoggingMiddleware, RateLimitMiddleware};
+use cli::{Cli, Command, ConfigAction, MigrateAction};
+use configs::database::DatabaseConfig;
+use configs::settings::Settings;
+use configs::{AppState, migrations};
+use middleware::{CorsMiddleware, LoggingMiddleware, RateLimitMiddleware, RequestIdMiddleware};
 use routes::configure_routes;
 
+/// Actix's default access log format plus the request id
+const ACCESS_LOG_FORMAT: &str =
+    r#"%a "%r" %s %b "%{Referer}i" "%{User-Agent}i" %T request_id=%{x-request-id}i"#;
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    dotenv().ok();
+    // Logging is configured by the settings, so report problems on stderr.
+    // A missing .env is fine; a malformed one would silently drop every later variable.
+    match dotenv() {
+        Err(dotenv::Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            eprintln!("Failed to load .env: {}", e);
+            std::process::exit(1);
+        }
+        Ok(_) => {}
+    }
+    let cli = Cli::parse();
+
+    let settings = match Settings::load(&cli.overrides) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Configuration error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(Command::Config {
+        action: ConfigAction::Print,
+    }) = cli.command
+    {
+        if let Some(source) = &settings.source {
+            println!("# Settings file: {}", source.display());
+        }
+        print!("{}", settings.to_redacted_toml());
+        return Ok(());
+    }
 
     // Initialize logging
-    let logging_config = LoggingConfig::from_env();
-    logging_config.init();
+    let tracer_provider = settings.logging.init();
+
+    if let Some(Command::Migrate { action }) = cli.command {
+        return run_migrate(&settings.database, action).await;
+    }
 
     tracing::info!("🚀 Starting Rust REST API");
+    match &settings.source {
+        Some(source) => tracing::info!("✓ Settings loaded from {}", source.display()),
+        None => tracing::info!("✓ Settings loaded from defaults and environment"),
+    }
 
     // Initialize application state
-    let state = match AppState::new().await {
+    let state = match AppState::new(&settings).await {
         Ok(state) => {
             tracing::info!("✓ Application state initialized");
             state
@@ -36,19 +87,116 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
-    let bind_address = "0.0.0.0:8080";
+    let rate_limits = settings.rate_limit.clone();
+    let shutdown_config = settings.shutdown.clone();
+    let environment = settings.server.environment;
+    let cors = settings.cors.clone();
+
+    // Kept outside the app factory for the startup probe and the shutdown sequence
+    let probes = state.probes.clone();
+    let background = state.shutdown.clone();
+    let db = state.db.clone();
+
+    let bind_address = settings.server.bind_address.as_str();
     tracing::info!("🚀 Starting server at http://{}", bind_address);
 
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         App::new()
-            .app_data(actix_web::web::Data::new(state.clone()))
-            .wrap(CorsMiddleware::from_env()) // ← CORS middleware
-            .wrap(Logger::default()) // ← Actix's built-in logger
-            .wrap(LoggingMiddleware) // ← Custom logging middleware
-            .wrap(RateLimitMiddleware::new(100)) // 100 requests per minute
+            .app_data(web::Data::new(state.clone()))
+            .app_data(web::JsonConfig::default().error_handler(errors::json_error_handler))
+            .app_data(web::PathConfig::default().error_handler(errors::path_error_handler))
+            .app_data(web::QueryConfig::default().error_handler(errors::query_error_handler))
+            .wrap(CorsMiddleware::for_environment(environment, &cors)) // ← CORS middleware
+            .wrap(Logger::new(ACCESS_LOG_FORMAT)) // ← Actix's built-in logger
+            .wrap(RateLimitMiddleware::new(rate_limits.clone())) // ← Per-route rate-limit policies
+            .wrap(LoggingMiddleware) // ← Custom logging middleware, renders errors with the request id in scope
+            .wrap(RequestIdMiddleware) // ← Outermost so every layer sees the same X-Request-Id
             .configure(configure_routes)
     })
+    // Signals are handled below so readiness can fail before the listener closes
+    .disable_signals()
+    .shutdown_timeout(shutdown_config.drain_timeout_secs)
     .bind(bind_address)?
-    .run()
-    .await
+    .run();
+
+    probes.mark_started();
+
+    let handle = server.handle();
+    actix_web::rt::spawn(async move {
+        shutdown::signal().await;
+
+        tracing::info!(
+            delay_secs = shutdown_config.readiness_delay_secs,
+            "Shutdown 1/4: readiness failing, waiting for load balancers to stop routing"
+        );
+        probes.begin_draining();
+        // A second signal skips the wait and drops in-flight requests
+        let graceful = tokio::select! {
+            _ = tokio::time::sleep(shutdown_config.readiness_delay()) => true,
+            _ = shutdown::signal() => false,
+        };
+
+        tracing::info!(
+            timeout_secs = shutdown_config.drain_timeout_secs,
+            graceful,
+            "Shutdown 2/4: draining in-flight HTTP requests"
+        );
+        handle.stop(graceful).await;
+    });
+
+    let server = server.await;
+    tracing::info!("✓ HTTP server stopped");
+
+    tracing::info!(
+        tasks = background.task_count(),
+        timeout_secs = settings.shutdown.task_timeout_secs,
+        "Shutdown 3/4: cancelling background tasks"
+    );
+    if !background.cancel(settings.shutdown.task_timeout()).await {
+        tracing::warn!("Background tasks still running after timeout; abandoning them");
+    }
+
+    // The Redis connection manager has no close; it goes with the last AppState
+    // clone, which the stopped workers have already dropped
+    tracing::info!("Shutdown 4/4: closing database pool");
+    db.close().await;
+    tracing::info!("✓ Shutdown complete");
+
+    // Flush spans still buffered in the batch exporter
+    if let Some(provider) = tracer_provider
+        && let Err(e) = provider.shutdown()
+    {
+        eprintln!("Failed to shut down tracer provider: {}", e);
+    }
+
+    server
+}
+
+async fn run_migrate(database: &DatabaseConfig, action: MigrateAction) -> std::io::Result<()> {
+    let pool = database.connect().await.map_err(std::io::Error::other)?;
+
+    match action {
+        MigrateAction::Up => migrations::run(&pool).await,
+        MigrateAction::Down => match migrations::revert_last(&pool).await {
+            Ok(None) => {
+                println!("No applied migrations to revert");
+                Ok(())
+            }
+            result => result.map(|_| ()),
+        },
+        MigrateAction::Status => migrations::status(&pool).await.map(|statuses| {
+            for status in statuses {
+                println!(
+                    "{:>6}  {:<8}  {}",
+                    status.version,
+                    if status.applied { "applied" } else { "pending" },
+                    status.description
+                );
+            }
+        }),
+    }
+    .map_err(std::io::Error::other)?;
+
+    pool.close().await;
+    Ok(())
 }
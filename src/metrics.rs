@@ -0,0 +1,165 @@
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use std::future::Future;
+use std::sync::LazyLock;
+use std::time::Instant;
+use tracing::Instrument;
+
+use crate::configs::AppState;
+
+/// Process-wide metrics, rendered in Prometheus text format at `/metrics`
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub struct Metrics {
+    registry: Registry,
+    pub http_requests_total: IntCounterVec,
+    pub http_request_duration_seconds: HistogramVec,
+    pub db_pool_size: IntGauge,
+    pub db_pool_idle: IntGauge,
+    pub db_pool_max: IntGauge,
+    pub redis_command_duration_seconds: HistogramVec,
+    pub rate_limit_rejections_total: IntCounterVec,
+    pub login_attempts_total: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests_total = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled"),
+            &["method", "route", "status"],
+        )
+        .expect("valid metric");
+        let http_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency in seconds",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("valid metric");
+
+        // sqlx does not expose how many tasks are waiting on `acquire`; a pool
+        // at `db_pool_max` with no idle connections is the saturation signal
+        let db_pool_size =
+            IntGauge::new("db_pool_size", "Open database connections").expect("valid metric");
+        let db_pool_idle =
+            IntGauge::new("db_pool_idle", "Idle database connections").expect("valid metric");
+        let db_pool_max =
+            IntGauge::new("db_pool_max", "Maximum database connections").expect("valid metric");
+
+        let redis_command_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "redis_command_duration_seconds",
+                "Redis command latency in seconds",
+            )
+            .buckets(vec![
+                0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0,
+            ]),
+            &["command", "outcome"],
+        )
+        .expect("valid metric");
+
+        let rate_limit_rejections_total = IntCounterVec::new(
+            Opts::new(
+                "rate_limit_rejections_total",
+                "Requests rejected by the rate limiter",
+            ),
+            &["policy"],
+        )
+        .expect("valid metric");
+        let login_attempts_total = IntCounterVec::new(
+            Opts::new("login_attempts_total", "Login attempts"),
+            &["outcome"],
+        )
+        .expect("valid metric");
+
+        registry
+            .register(Box::new(http_requests_total.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(http_request_duration_seconds.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(db_pool_size.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(db_pool_idle.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(db_pool_max.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(redis_command_duration_seconds.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(rate_limit_rejections_total.clone()))
+            .expect("metric registered once");
+        registry
+            .register(Box::new(login_attempts_total.clone()))
+            .expect("metric registered once");
+
+        Self {
+            registry,
+            http_requests_total,
+            http_request_duration_seconds,
+            db_pool_size,
+            db_pool_idle,
+            db_pool_max,
+            redis_command_duration_seconds,
+            rate_limit_rejections_total,
+            login_attempts_total,
+        }
+    }
+
+    /// Record one completed HTTP request; `route` is the matched pattern, not the raw path
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        let status = status.to_string();
+        let labels = [method, route, status.as_str()];
+
+        self.http_requests_total.with_label_values(&labels).inc();
+        self.http_request_duration_seconds
+            .with_label_values(&labels)
+            .observe(seconds);
+    }
+
+    /// Sample pool gauges and encode every metric in the Prometheus text format
+    pub fn render(&self, state: &AppState) -> Result<String, prometheus::Error> {
+        self.db_pool_size.set(state.db.size() as i64);
+        self.db_pool_idle.set(state.db.num_idle() as i64);
+        self.db_pool_max
+            .set(state.db.options().get_max_connections() as i64);
+
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer).expect("text format is UTF-8"))
+    }
+}
+
+/// Time a Redis command, labelled by command name and whether it succeeded, and
+/// trace it as a child span of the current request
+pub async fn time_redis<T, E>(
+    command: &'static str,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let span = tracing::info_span!(
+        "redis",
+        otel.name = command,
+        otel.kind = "client",
+        db.system = "redis",
+        db.operation = command,
+    );
+
+    let start = Instant::now();
+    let result = future.instrument(span).await;
+    let outcome = if result.is_ok() { "ok" } else { "error" };
+
+    METRICS
+        .redis_command_duration_seconds
+        .with_label_values(&[command, outcome])
+        .observe(start.elapsed().as_secs_f64());
+
+    result
+}
@@ -1,19 +1,60 @@
-use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
-use actix_web::{Error as ActixError, web};
+use actix_web::dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::{Error as ActixError, FromRequest, HttpMessage, HttpRequest, web};
 use futures::future::LocalBoxFuture;
 use std::future::{Ready, ready};
+use std::rc::Rc;
+use uuid::Uuid;
 
 use crate::configs::AppState;
+use crate::errors::AppError;
+use crate::models::Claims;
 use crate::services::AuthService;
 
-// Re-export Claims for convenience
-// pub use crate::models::Claims;
+/// Authenticated caller, extracted from the `Claims` stored by `AuthMiddleware`.
+///
+/// Only usable on routes wrapped by `AuthMiddleware`; elsewhere extraction
+/// fails with 401.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: Uuid,
+    pub claims: Claims,
+}
+
+impl FromRequest for AuthUser {
+    type Error = ActixError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let auth_user = req
+            .extensions()
+            .get::<Claims>()
+            .and_then(|claims| {
+                Uuid::parse_str(&claims.user_id)
+                    .ok()
+                    .map(|user_id| AuthUser {
+                        user_id,
+                        claims: claims.clone(),
+                    })
+            })
+            .ok_or_else(|| AppError::Unauthorized("Not authenticated".to_string()).into());
+
+        ready(auth_user)
+    }
+}
+
+/// Routes reachable without a bearer token. `/api/auth/sessions` manages the
+/// caller's own sessions, so it is the one authenticated route under `/api/auth/`.
+pub fn is_public_path(path: &str) -> bool {
+    (path.starts_with("/api/auth/") && !path.starts_with("/api/auth/sessions"))
+        || path == "/api/health"
+        || path == "/"
+}
 
 pub struct AuthMiddleware;
 
 impl<S, B> Transform<S, ServiceRequest> for AuthMiddleware
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -24,17 +65,19 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(AuthMiddlewareService { service }))
+        ready(Ok(AuthMiddlewareService {
+            service: Rc::new(service),
+        }))
     }
 }
 
 pub struct AuthMiddlewareService<S> {
-    service: S,
+    service: Rc<S>,
 }
 
 impl<S, B> Service<ServiceRequest> for AuthMiddlewareService<S>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError> + 'static,
     S::Future: 'static,
     B: 'static,
 {
@@ -47,8 +90,7 @@ where
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let path = req.path().to_string();
 
-        // Skip authentication for public routes
-        if path.starts_with("/api/auth/") || path == "/health" || path == "/" {
+        if is_public_path(&path) {
             let fut = self.service.call(req);
             return Box::pin(async move {
                 let res = fut.await?;
@@ -70,26 +112,23 @@ where
 
         if token.is_none() {
             return Box::pin(async move {
-                Err(actix_web::error::ErrorUnauthorized(
-                    "Missing authorization token",
-                ))
+                Err(AppError::Unauthorized("Missing authorization token".to_string()).into())
             });
         }
 
         let token = token.unwrap();
         let state = req.app_data::<web::Data<AppState>>().unwrap().clone();
-
-        let fut = self.service.call(req);
+        let service = Rc::clone(&self.service);
 
         Box::pin(async move {
             match AuthService::validate_token(&state, &token).await {
-                Ok(_) => {
-                    let res = fut.await?;
+                Ok(claims) => {
+                    // Make the validated claims available to downstream guards
+                    req.extensions_mut().insert(claims);
+                    let res = service.call(req).await?;
                     Ok(res)
                 }
-                Err(_) => Err(actix_web::error::ErrorUnauthorized(
-                    "Invalid or expired token",
-                )),
+                Err(e) => Err(e.into()),
             }
         })
     }
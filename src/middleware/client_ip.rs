@@ -0,0 +1,144 @@
+use actix_web::dev::Payload;
+use actix_web::http::header::{FORWARDED, HeaderMap, USER_AGENT};
+use actix_web::{FromRequest, HttpMessage, HttpRequest, web};
+use std::fmt;
+use std::future::{Ready, ready};
+use std::net::{IpAddr, SocketAddr};
+
+use crate::configs::AppState;
+use crate::configs::proxy::{ForwardedHeader, ProxyConfig};
+
+/// Address of the client that originated a request, without the port.
+///
+/// Forwarding headers are only honoured when the connection comes from a
+/// trusted proxy, so clients cannot spoof their address by sending them.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl ClientIp {
+    /// Resolve once per request; later calls reuse the cached result
+    pub fn resolve(req: &HttpRequest) -> Self {
+        if let Some(client_ip) = req.extensions().get::<ClientIp>() {
+            return *client_ip;
+        }
+
+        let peer = req.peer_addr().map(|addr| addr.ip());
+        let client_ip = match (peer, req.app_data::<web::Data<AppState>>()) {
+            (Some(peer), Some(state)) => ClientIp(Some(resolve(peer, req.headers(), &state.proxy))),
+            _ => ClientIp(peer),
+        };
+
+        req.extensions_mut().insert(client_ip);
+        client_ip
+    }
+}
+
+impl fmt::Display for ClientIp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(ip) => ip.fmt(f),
+            None => f.write_str("unknown"),
+        }
+    }
+}
+
+impl FromRequest for ClientIp {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(ClientIp::resolve(req)))
+    }
+}
+
+/// Longest `User-Agent` kept with a session
+const MAX_USER_AGENT_LEN: usize = 256;
+
+/// Who is signing in, recorded with each session
+#[derive(Debug, Clone)]
+pub struct ClientInfo {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
+
+impl FromRequest for ClientInfo {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let user_agent = req
+            .headers()
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(MAX_USER_AGENT_LEN).collect());
+
+        ready(Ok(ClientInfo {
+            ip: ClientIp::resolve(req).0,
+            user_agent,
+        }))
+    }
+}
+
+/// Walk the forwarding chain from the nearest hop outwards and return the first
+/// address that is not a trusted proxy
+fn resolve(peer: IpAddr, headers: &HeaderMap, proxies: &ProxyConfig) -> IpAddr {
+    if !proxies.is_trusted(peer) {
+        return peer;
+    }
+
+    let mut client = peer;
+    for hop in forwarded_chain(headers, proxies.forwarded_header)
+        .iter()
+        .rev()
+    {
+        // An obfuscated or malformed hop ends the chain we can vouch for
+        let Some(ip) = parse_node(hop) else {
+            break;
+        };
+        client = ip;
+        if !proxies.is_trusted(ip) {
+            break;
+        }
+    }
+
+    client
+}
+
+/// Client addresses listed in `header`, oldest first. Only the header the
+/// proxies are configured to write is read: a proxy passes any other through
+/// untouched, so its contents come straight from the client.
+fn forwarded_chain(headers: &HeaderMap, header: ForwardedHeader) -> Vec<String> {
+    match header {
+        ForwardedHeader::Forwarded => headers
+            .get_all(FORWARDED)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (name, value) = pair.trim().split_once('=')?;
+                    name.eq_ignore_ascii_case("for")
+                        .then(|| value.trim().trim_matches('"').to_string())
+                })
+            })
+            .collect(),
+        ForwardedHeader::XForwardedFor => headers
+            .get_all("x-forwarded-for")
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|hop| hop.trim().to_string())
+            .filter(|hop| !hop.is_empty())
+            .collect(),
+    }
+}
+
+/// Parse `203.0.113.7`, `203.0.113.7:4711`, `2001:db8::1` or `[2001:db8::1]:4711`
+fn parse_node(node: &str) -> Option<IpAddr> {
+    node.parse::<IpAddr>()
+        .or_else(|_| node.parse::<SocketAddr>().map(|addr| addr.ip()))
+        .or_else(|_| {
+            node.trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+        })
+        .ok()
+}
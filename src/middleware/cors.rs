@@ -1,6 +1,10 @@
 use actix_cors::Cors;
 use actix_web::http;
 
+use crate::configs::cors::CorsConfig;
+use crate::configs::server::Environment;
+use crate::middleware::request_id::REQUEST_ID_HEADER;
+
 pub struct CorsMiddleware;
 
 impl CorsMiddleware {
@@ -14,16 +18,11 @@ impl CorsMiddleware {
     }
 
     /// Create a strict CORS configuration for production
-    pub fn strict() -> Cors {
-        let allowed_origins = std::env::var("ALLOWED_ORIGINS")
-            .unwrap_or_else(|_| "http://localhost:3000".to_string());
-
-        let origins: Vec<&str> = allowed_origins.split(',').collect();
-
+    pub fn strict(config: &CorsConfig) -> Cors {
         let mut cors = Cors::default();
 
-        for origin in origins {
-            cors = cors.allowed_origin(origin.trim());
+        for origin in &config.allowed_origins {
+            cors = cors.allowed_origin(origin);
         }
 
         cors.allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"])
@@ -31,17 +30,17 @@ impl CorsMiddleware {
                 http::header::AUTHORIZATION,
                 http::header::ACCEPT,
                 http::header::CONTENT_TYPE,
+                REQUEST_ID_HEADER.clone(),
             ])
+            .expose_headers(vec![REQUEST_ID_HEADER.clone()])
             .max_age(3600)
     }
 
     /// Create CORS configuration based on environment
-    pub fn from_env() -> Cors {
-        let env = std::env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
-
-        match env.as_str() {
-            "production" => Self::strict(),
-            _ => Self::permissive(),
+    pub fn for_environment(environment: Environment, config: &CorsConfig) -> Cors {
+        match environment {
+            Environment::Production => Self::strict(config),
+            Environment::Development => Self::permissive(),
         }
     }
 
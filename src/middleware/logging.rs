@@ -1,19 +1,60 @@
-use actix_web::Error as ActixError;
+use actix_web::body::{EitherBody, MessageBody};
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use actix_web::{Error as ActixError, HttpMessage};
 use futures::future::LocalBoxFuture;
+use opentelemetry::global;
+use opentelemetry::propagation::{Extractor, Injector};
 use std::future::{Ready, ready};
 use std::time::Instant;
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
-/// Custom logging middleware for detailed request/response logging
+use crate::metrics::METRICS;
+use crate::middleware::{ClientIp, RequestId};
+
+/// Custom logging middleware for detailed request/response logging.
+///
+/// Each request runs in an `http_request` span that carries the id assigned by
+/// `RequestIdMiddleware` and continues the caller's `traceparent`, if any.
+/// Errors from inner services are rendered here, while the request id is still
+/// in scope, so their bodies include it.
 pub struct LoggingMiddleware;
 
+/// Reads W3C trace context from request headers
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+/// Writes W3C trace context into response headers
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
 impl<S, B> Transform<S, ServiceRequest> for LoggingMiddleware
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
     S::Future: 'static,
-    B: 'static,
+    B: MessageBody + 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = ActixError;
     type InitError = ();
     type Transform = LoggingMiddlewareService<S>;
@@ -32,9 +73,9 @@ impl<S, B> Service<ServiceRequest> for LoggingMiddlewareService<S>
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
     S::Future: 'static,
-    B: 'static,
+    B: MessageBody + 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = ActixError;
     type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
 
@@ -42,15 +83,22 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let start_time = Instant::now();
+        let request_id = req
+            .extensions()
+            .get::<RequestId>()
+            .map(|id| id.to_string())
+            .unwrap_or_default();
         let method = req.method().to_string();
         let path = req.path().to_string();
         let query = req.query_string().to_string();
 
+        // Label by route pattern so ids in paths don't explode cardinality
+        let route = req
+            .match_pattern()
+            .unwrap_or_else(|| "unmatched".to_string());
+
         // Get client IP
-        let peer_addr = req
-            .peer_addr()
-            .map(|addr| addr.to_string())
-            .unwrap_or_else(|| "unknown".to_string());
+        let client_ip = ClientIp::resolve(req.request());
 
         // Get user agent
         let user_agent = req
@@ -62,62 +110,97 @@ where
 
         // Log request
         tracing::info!(
+            request_id = %request_id,
             method = %method,
             path = %path,
             query = %query,
-            ip = %peer_addr,
+            ip = %client_ip,
             user_agent = %user_agent,
             "Incoming request"
         );
 
-        let fut = self.service.call(req);
+        let span = tracing::info_span!(
+            "http_request",
+            otel.name = %format!("{} {}", method, route),
+            otel.kind = "server",
+            http.request.method = %method,
+            http.route = %route,
+            url.path = %path,
+            client.address = %client_ip,
+            request_id = %request_id,
+            http.response.status_code = tracing::field::Empty,
+        );
+        let parent = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(req.headers()))
+        });
+        // Fails only when no OpenTelemetry layer is installed
+        let _ = span.set_parent(parent);
 
-        Box::pin(async move {
-            let result = fut.await;
-            let elapsed = start_time.elapsed();
+        let http_req = req.request().clone();
+        let fut = span.in_scope(|| self.service.call(req));
 
-            match &result {
-                Ok(response) => {
-                    let status = response.status();
-
-                    if status.is_success() {
-                        tracing::info!(
-                            method = %method,
-                            path = %path,
-                            status = %status.as_u16(),
-                            duration_ms = %elapsed.as_millis(),
-                            "Request completed successfully"
-                        );
-                    } else if status.is_client_error() {
-                        tracing::warn!(
-                            method = %method,
-                            path = %path,
-                            status = %status.as_u16(),
-                            duration_ms = %elapsed.as_millis(),
-                            "Client error"
-                        );
-                    } else if status.is_server_error() {
-                        tracing::error!(
-                            method = %method,
-                            path = %path,
-                            status = %status.as_u16(),
-                            duration_ms = %elapsed.as_millis(),
-                            "Server error"
-                        );
-                    }
-                }
+        let request_span = span.clone();
+        let handled = async move {
+            let mut response = match fut.await {
+                Ok(response) => response.map_into_left_body(),
                 Err(error) => {
                     tracing::error!(
+                        request_id = %request_id,
                         method = %method,
                         path = %path,
                         error = %error,
-                        duration_ms = %elapsed.as_millis(),
                         "Request failed with error"
                     );
+                    // Render here so the error body can carry the request id
+                    ServiceResponse::from_err(error, http_req).map_into_right_body()
                 }
+            };
+
+            let elapsed = start_time.elapsed();
+            let status = response.status();
+
+            METRICS.observe_request(&method, &route, status.as_u16(), elapsed.as_secs_f64());
+
+            request_span.record("http.response.status_code", status.as_u16());
+            global::get_text_map_propagator(|propagator| {
+                propagator.inject_context(
+                    &request_span.context(),
+                    &mut HeaderInjector(response.headers_mut()),
+                )
+            });
+
+            if status.is_success() {
+                tracing::info!(
+                    request_id = %request_id,
+                    method = %method,
+                    path = %path,
+                    status = %status.as_u16(),
+                    duration_ms = %elapsed.as_millis(),
+                    "Request completed successfully"
+                );
+            } else if status.is_client_error() {
+                tracing::warn!(
+                    request_id = %request_id,
+                    method = %method,
+                    path = %path,
+                    status = %status.as_u16(),
+                    duration_ms = %elapsed.as_millis(),
+                    "Client error"
+                );
+            } else if status.is_server_error() {
+                tracing::error!(
+                    request_id = %request_id,
+                    method = %method,
+                    path = %path,
+                    status = %status.as_u16(),
+                    duration_ms = %elapsed.as_millis(),
+                    "Server error"
+                );
             }
 
-            result
-        })
+            Ok(response)
+        };
+
+        Box::pin(handled.instrument(span))
     }
 }
@@ -1,9 +1,15 @@
 mod auth;
+mod client_ip;
 mod cors;
 mod logging;
+mod permission;
 mod rate_limit;
+mod request_id;
 
-pub use auth::AuthMiddleware;
+pub use auth::{AuthMiddleware, AuthUser, is_public_path};
+pub use client_ip::{ClientInfo, ClientIp};
 pub use cors::CorsMiddleware;
 pub use logging::LoggingMiddleware;
+pub use permission::RequirePermission;
 pub use rate_limit::RateLimitMiddleware;
+pub use request_id::{RequestId, RequestIdMiddleware, current_request_id};
@@ -0,0 +1,86 @@
+use actix_web::Error as ActixError;
+use actix_web::HttpMessage;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use futures::future::LocalBoxFuture;
+use std::future::{Ready, ready};
+
+use crate::errors::AppError;
+use crate::models::Claims;
+
+/// Per-route guard that rejects requests whose token lacks a permission.
+///
+/// Must run inside `AuthMiddleware`, which stores the validated `Claims`
+/// in the request extensions.
+pub struct RequirePermission {
+    permission: &'static str,
+}
+
+impl RequirePermission {
+    pub fn new(permission: &'static str) -> Self {
+        Self { permission }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequirePermission
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = ActixError;
+    type InitError = ();
+    type Transform = RequirePermissionService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequirePermissionService {
+            service,
+            permission: self.permission,
+        }))
+    }
+}
+
+pub struct RequirePermissionService<S> {
+    service: S,
+    permission: &'static str,
+}
+
+impl<S, B> Service<ServiceRequest> for RequirePermissionService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = ActixError;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let allowed = req
+            .extensions()
+            .get::<Claims>()
+            .is_some_and(|claims| claims.has_permission(self.permission));
+
+        if !allowed {
+            tracing::warn!(
+                path = %req.path(),
+                permission = %self.permission,
+                "Permission denied"
+            );
+
+            return Box::pin(async move {
+                Err(AppError::Forbidden("Insufficient permissions".to_string()).into())
+            });
+        }
+
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            Ok(res)
+        })
+    }
+}
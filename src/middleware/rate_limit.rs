@@ -1,35 +1,224 @@
-use actix_web::Error as ActixError;
+use actix_web::body::{EitherBody, MessageBody};
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+use actix_web::{Error as ActixError, web};
 use futures::future::LocalBoxFuture;
-use std::collections::HashMap;
+use redis::aio::ConnectionManager;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
 use std::future::{Ready, ready};
-use std::sync::{Arc, Mutex};
+use std::rc::Rc;
+use std::sync::{Arc, LazyLock, Mutex};
 use std::time::{Duration, Instant};
+use uuid::Uuid;
 
-/// Simple rate limiting middleware
-pub struct RateLimitMiddleware {
-    requests_per_minute: usize,
+use crate::configs::AppState;
+use crate::configs::rate_limit::{RateLimitConfig, RateLimitKey};
+use crate::errors::AppError;
+use crate::metrics::{METRICS, time_redis};
+use crate::middleware::ClientIp;
+use crate::services::AuthService;
+
+/// Sliding-window log kept in a sorted set, evaluated atomically on the Redis server.
+///
+/// Uses the Redis clock so every instance agrees on the window. Returns
+/// `{allowed, remaining, reset_ms}`.
+static SLIDING_WINDOW_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r"
+        local key = KEYS[1]
+        local window = tonumber(ARGV[1])
+        local limit = tonumber(ARGV[2])
+        local member = ARGV[3]
+
+        local time = redis.call('TIME')
+        local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+        redis.call('ZREMRANGEBYSCORE', key, '-inf', now - window)
+        local count = redis.call('ZCARD', key)
+
+        local allowed = 0
+        if count < limit then
+            redis.call('ZADD', key, now, member)
+            redis.call('PEXPIRE', key, window)
+            count = count + 1
+            allowed = 1
+        end
+
+        local oldest = redis.call('ZRANGE', key, 0, 0, 'WITHSCORES')
+        local reset = window
+        if oldest[2] then
+            reset = tonumber(oldest[2]) + window - now
+        end
+
+        return {allowed, limit - count, reset}
+        ",
+    )
+});
+
+static RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+static RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+static RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+static API_KEY: HeaderName = HeaderName::from_static("x-api-key");
+
+/// Outcome of a rate-limit check
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: usize,
+    pub remaining: usize,
+    pub reset: Duration,
 }
 
-impl RateLimitMiddleware {
-    pub fn new(requests_per_minute: usize) -> Self {
+impl RateLimitDecision {
+    /// Add `RateLimit-*` headers, plus `Retry-After` when the request was rejected
+    fn apply_headers(&self, headers: &mut HeaderMap) {
+        let reset_secs = self.reset.as_millis().div_ceil(1000);
+
+        headers.insert(RATE_LIMIT_LIMIT.clone(), HeaderValue::from(self.limit));
+        headers.insert(
+            RATE_LIMIT_REMAINING.clone(),
+            HeaderValue::from(self.remaining),
+        );
+        headers.insert(
+            RATE_LIMIT_RESET.clone(),
+            HeaderValue::from(reset_secs as u64),
+        );
+
+        if !self.allowed {
+            headers.insert(RETRY_AFTER, HeaderValue::from(reset_secs as u64));
+        }
+    }
+}
+
+/// Sliding-window limiter backed by Redis, with a per-process fallback when
+/// Redis is unreachable
+#[derive(Clone)]
+pub struct SlidingWindowLimiter {
+    fallback: Arc<Mutex<FallbackWindows>>,
+}
+
+struct FallbackWindows {
+    windows: HashMap<String, VecDeque<Instant>>,
+    last_sweep: Instant,
+}
+
+impl SlidingWindowLimiter {
+    pub fn new() -> Self {
         Self {
-            requests_per_minute,
+            fallback: Arc::new(Mutex::new(FallbackWindows {
+                windows: HashMap::new(),
+                last_sweep: Instant::now(),
+            })),
+        }
+    }
+
+    pub async fn check(
+        &self,
+        redis: Option<ConnectionManager>,
+        key: &str,
+        limit: usize,
+        window: Duration,
+    ) -> RateLimitDecision {
+        if let Some(mut conn) = redis {
+            let result: Result<(i64, i64, i64), _> = time_redis(
+                "EVALSHA",
+                SLIDING_WINDOW_SCRIPT
+                    .key(format!("rate_limit:{}", key))
+                    .arg(window.as_millis() as u64)
+                    .arg(limit)
+                    .arg(Uuid::new_v4().to_string())
+                    .invoke_async(&mut conn),
+            )
+            .await;
+
+            match result {
+                Ok((allowed, remaining, reset_ms)) => {
+                    return RateLimitDecision {
+                        allowed: allowed == 1,
+                        limit,
+                        remaining: remaining.max(0) as usize,
+                        reset: Duration::from_millis(reset_ms.max(0) as u64),
+                    };
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        error = %e,
+                        "Redis rate limiter unavailable, using in-memory fallback"
+                    );
+                }
+            }
+        }
+
+        self.check_in_memory(key, limit, window)
+    }
+
+    fn check_in_memory(&self, key: &str, limit: usize, window: Duration) -> RateLimitDecision {
+        let mut fallback = self.fallback.lock().unwrap();
+        let now = Instant::now();
+
+        // Evict idle clients once per window so the map cannot grow without bound
+        if now.duration_since(fallback.last_sweep) >= window {
+            fallback.windows.retain(|_, requests| {
+                requests
+                    .back()
+                    .is_some_and(|&last| now.duration_since(last) < window)
+            });
+            fallback.last_sweep = now;
+        }
+
+        let requests = fallback.windows.entry(key.to_string()).or_default();
+        while requests
+            .front()
+            .is_some_and(|&time| now.duration_since(time) >= window)
+        {
+            requests.pop_front();
+        }
+
+        let allowed = requests.len() < limit;
+        if allowed {
+            requests.push_back(now);
+        }
+
+        let reset = requests
+            .front()
+            .map(|&oldest| window.saturating_sub(now.duration_since(oldest)))
+            .unwrap_or(window);
+
+        RateLimitDecision {
+            allowed,
+            limit,
+            remaining: limit.saturating_sub(requests.len()),
+            reset,
         }
     }
 }
 
-struct RateLimitData {
-    requests: Vec<Instant>,
+/// Rate limiting middleware shared across instances through Redis.
+///
+/// Each request is counted against the first matching policy, keyed by client
+/// IP, user or API key as the policy specifies.
+pub struct RateLimitMiddleware {
+    config: Rc<RateLimitConfig>,
+    limiter: SlidingWindowLimiter,
+}
+
+impl RateLimitMiddleware {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: Rc::new(config),
+            limiter: SlidingWindowLimiter::new(),
+        }
+    }
 }
 
 impl<S, B> Transform<S, ServiceRequest> for RateLimitMiddleware
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError> + 'static,
     S::Future: 'static,
-    B: 'static,
+    B: MessageBody + 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = ActixError;
     type InitError = ();
     type Transform = RateLimitMiddlewareService<S>;
@@ -37,72 +226,127 @@ where
 
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(RateLimitMiddlewareService {
-            service,
-            limits: Arc::new(Mutex::new(HashMap::new())),
-            requests_per_minute: self.requests_per_minute,
+            service: Rc::new(service),
+            config: Rc::clone(&self.config),
+            limiter: self.limiter.clone(),
         }))
     }
 }
 
 pub struct RateLimitMiddlewareService<S> {
-    service: S,
-    limits: Arc<Mutex<HashMap<String, RateLimitData>>>,
-    requests_per_minute: usize,
+    service: Rc<S>,
+    config: Rc<RateLimitConfig>,
+    limiter: SlidingWindowLimiter,
+}
+
+/// Identity a request is counted against, e.g. `ip:203.0.113.7` or `user:<uuid>`
+fn identity(req: &ServiceRequest, key: RateLimitKey, ip: &str) -> String {
+    match key {
+        RateLimitKey::Ip => {}
+        RateLimitKey::User => {
+            // Runs before AuthMiddleware, so verify the token here; an invalid
+            // token is counted against the client IP instead
+            let user_id = req
+                .headers()
+                .get(AUTHORIZATION)
+                .and_then(|h| h.to_str().ok())
+                .and_then(|h| h.strip_prefix("Bearer "))
+                .zip(req.app_data::<web::Data<AppState>>())
+                .and_then(|(token, state)| AuthService::decode_token(state, token).ok())
+                .map(|claims| claims.user_id);
+
+            if let Some(user_id) = user_id {
+                return format!("user:{}", user_id);
+            }
+        }
+        RateLimitKey::ApiKey => {
+            // Hash the key so raw credentials never end up in Redis
+            if let Some(api_key) = req.headers().get(&API_KEY).map(|h| h.as_bytes()) {
+                return format!("api_key:{}", hex_digest(api_key));
+            }
+        }
+    }
+
+    format!("ip:{}", ip)
+}
+
+fn hex_digest(value: &[u8]) -> String {
+    Sha256::digest(value)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
 }
 
 impl<S, B> Service<ServiceRequest> for RateLimitMiddlewareService<S>
 where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError> + 'static,
     S::Future: 'static,
-    B: 'static,
+    B: MessageBody + 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = ActixError;
     type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
 
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let ip = req
-            .peer_addr()
-            .map(|addr| addr.to_string())
-            .unwrap_or_else(|| "unknown".to_string());
-
-        let mut limits = self.limits.lock().unwrap();
-        let now = Instant::now();
-        let one_minute_ago = now - Duration::from_secs(60);
-
-        let data = limits.entry(ip.clone()).or_insert(RateLimitData {
-            requests: Vec::new(),
-        });
+        let client_ip = ClientIp::resolve(req.request()).0;
 
-        // Remove old requests
-        data.requests.retain(|&time| time > one_minute_ago);
+        let policy = match client_ip {
+            Some(ip) if self.config.is_exempt(ip) => None,
+            _ => self
+                .config
+                .find_policy(req.method().as_str(), req.path())
+                .cloned(),
+        };
 
-        if data.requests.len() >= self.requests_per_minute {
-            tracing::warn!(
-                ip = %ip,
-                requests = %data.requests.len(),
-                "Rate limit exceeded"
-            );
-
-            drop(limits); // Release lock
+        let service = Rc::clone(&self.service);
 
+        let Some(policy) = policy else {
             return Box::pin(async move {
-                Err(actix_web::error::ErrorTooManyRequests(
-                    "Rate limit exceeded. Please try again later.",
-                ))
+                let res = service.call(req).await?;
+                Ok(res.map_into_left_body())
             });
-        }
+        };
 
-        data.requests.push(now);
-        drop(limits); // Release lock
+        let ip = client_ip
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let key = format!("{}:{}", policy.name, identity(&req, policy.key, &ip));
 
-        let fut = self.service.call(req);
+        let redis = req
+            .app_data::<web::Data<AppState>>()
+            .map(|state| state.redis.clone());
+        let limiter = self.limiter.clone();
 
         Box::pin(async move {
-            let res = fut.await?;
-            Ok(res)
+            let decision = limiter
+                .check(redis, &key, policy.requests, policy.window())
+                .await;
+
+            if !decision.allowed {
+                METRICS
+                    .rate_limit_rejections_total
+                    .with_label_values(&[policy.name.as_str()])
+                    .inc();
+                tracing::warn!(
+                    policy = %policy.name,
+                    key = %key,
+                    limit = %decision.limit,
+                    "Rate limit exceeded"
+                );
+
+                let mut res = req.error_response(AppError::TooManyRequests(
+                    "Rate limit exceeded. Please try again later.".to_string(),
+                    None,
+                ));
+                decision.apply_headers(res.headers_mut());
+                return Ok(res.map_into_right_body());
+            }
+
+            let mut res = service.call(req).await?;
+            decision.apply_headers(res.headers_mut());
+            Ok(res.map_into_left_body())
         })
     }
 }
@@ -0,0 +1,122 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error as ActixError, HttpMessage};
+use futures::future::LocalBoxFuture;
+use std::fmt;
+use std::future::{Ready, ready};
+use uuid::Uuid;
+
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest caller-supplied id we accept; anything else is replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    /// Id of the request currently being handled, set by `RequestIdMiddleware`
+    static REQUEST_ID: String;
+}
+
+/// Id of the request being handled on this task, if any
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Correlation id of a request, available from request extensions
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// Reuse the caller's `X-Request-Id` when it is safe to log, otherwise generate one
+    fn from_request(req: &ServiceRequest) -> Self {
+        let supplied = req
+            .headers()
+            .get(&REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .filter(|id| is_valid(id));
+
+        match supplied {
+            Some(id) => RequestId(id.to_string()),
+            None => RequestId(Uuid::new_v4().to_string()),
+        }
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Accepts or generates an `X-Request-Id` and echoes it on the response.
+///
+/// Registered outermost so every other middleware, handler and error body sees
+/// the same id: it is stored in request extensions and in a task-local.
+pub struct RequestIdMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestIdMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = ActixError;
+    type InitError = ();
+    type Transform = RequestIdMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdMiddlewareService { service }))
+    }
+}
+
+pub struct RequestIdMiddlewareService<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = ActixError>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = ActixError;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let request_id = RequestId::from_request(&req);
+        req.extensions_mut().insert(request_id.clone());
+
+        // Normalise the request header so the access log records the same id
+        let header_value = HeaderValue::from_str(&request_id.0).ok();
+        if let Some(value) = &header_value {
+            req.headers_mut()
+                .insert(REQUEST_ID_HEADER.clone(), value.clone());
+        }
+
+        // Inner middleware may start work synchronously in `call`
+        let fut = REQUEST_ID.sync_scope(request_id.0.clone(), || self.service.call(req));
+
+        Box::pin(REQUEST_ID.scope(request_id.0.clone(), async move {
+            let mut res = fut.await?;
+
+            if let Some(value) = header_value {
+                res.headers_mut().insert(REQUEST_ID_HEADER.clone(), value);
+            }
+
+            Ok(res)
+        }))
+    }
+}
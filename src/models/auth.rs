@@ -1,20 +1,129 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
 
-#[derive(Debug, Deserialize)]
+use crate::validation::validate_password_strength;
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginDto {
+    #[schema(example = "admin")]
     pub username: String,
+    #[schema(format = Password)]
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RefreshTokenDto {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TokenResponse {
+    /// Bearer access token (JWT)
     pub token: String,
+    /// Access token lifetime in seconds
     pub expires_in: i64,
+    /// Single-use token for `/api/auth/refresh`
+    pub refresh_token: String,
+    /// Refresh token lifetime in seconds
+    pub refresh_expires_in: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Result of a password login: tokens, or a challenge when 2FA is enabled
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Tokens(TokenResponse),
+    MfaRequired(MfaChallenge),
+}
+
+/// Password accepted; complete the login at `/api/auth/login/mfa`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaChallenge {
+    /// Always `true`; distinguishes this from a token response
+    pub mfa_required: bool,
+    /// Single-use token identifying this login attempt
+    pub challenge_token: String,
+    /// Seconds left to submit the second factor
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MfaLoginDto {
+    pub challenge_token: String,
+    /// Current authenticator code, or an unused recovery code
+    #[schema(example = "123456")]
+    pub code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
     pub user_id: String,
+    pub family_id: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
     pub exp: usize,
 }
+
+impl Claims {
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+}
+
+/// Payload stored in Redis under `refresh:{token}`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshTokenData {
+    pub user_id: String,
+    pub username: String,
+    pub family_id: String,
+}
+
+/// Payload stored in Redis under `mfa_challenge:{token}`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaChallengeData {
+    pub user_id: String,
+    pub username: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ForgotPasswordDto {
+    #[validate(email, length(max = 255))]
+    #[schema(format = Email, example = "jdoe@example.com")]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ResetPasswordDto {
+    /// Token from the reset email
+    #[validate(length(min = 1))]
+    pub token: String,
+    #[validate(
+        length(min = 8, max = 72),
+        custom(function = "validate_password_strength")
+    )]
+    #[schema(format = Password, min_length = 8, max_length = 72)]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct VerifyEmailDto {
+    /// Token from the verification email
+    #[validate(length(min = 1))]
+    pub token: String,
+}
+
+/// Payload stored in Redis under `email_verification:{hash}`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmailVerificationData {
+    pub user_id: String,
+    /// The address the link was sent to; a changed email invalidates the link
+    pub email: String,
+}
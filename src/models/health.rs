@@ -0,0 +1,65 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Up,
+    /// Reachable but slower than the configured threshold, or out of capacity
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadinessStatus {
+    Ready,
+    Degraded,
+    Unavailable,
+    /// Shutting down; dependencies may be fine but no new traffic should arrive
+    Draining,
+}
+
+/// Connection pool statistics at the time of the check
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: usize,
+    pub max: u32,
+}
+
+/// Result of probing one dependency
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DependencyCheck {
+    pub status: CheckStatus,
+    /// Round-trip time of the probe query in milliseconds
+    pub latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool: Option<PoolStats>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Checks {
+    pub database: DependencyCheck,
+    pub redis: DependencyCheck,
+}
+
+/// Readiness report served by `/readyz`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Readiness {
+    pub status: ReadinessStatus,
+    pub checks: Checks,
+    pub timestamp: String,
+}
+
+/// Body of `/livez` and `/startupz`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProbeStatus {
+    #[schema(example = "ok")]
+    pub status: &'static str,
+    pub uptime_secs: u64,
+    #[schema(example = "0.1.0")]
+    pub version: &'static str,
+}
@@ -1,7 +1,20 @@
 pub mod auth;
+pub mod health;
+pub mod pagination;
 pub mod product;
+pub mod role;
+pub mod session;
+pub mod totp;
 pub mod user;
 
-pub use auth::{Claims, LoginDto, TokenResponse};
+pub use auth::{
+    Claims, EmailVerificationData, ForgotPasswordDto, LoginDto, LoginResponse, MfaChallenge,
+    MfaChallengeData, MfaLoginDto, RefreshTokenData, RefreshTokenDto, ResetPasswordDto,
+    TokenResponse, VerifyEmailDto,
+};
+pub use pagination::{Page, PageRequest};
 pub use product::{CreateProductDto, Product, ProductQuery, UpdateProductDto};
-pub use user::{CreateUserDto, UpdateUserDto, User};
+pub use role::{AssignRoleDto, Role};
+pub use session::{RevokeSessionsQuery, Session};
+pub use totp::{RecoveryCodes, TotpCodeDto, TotpEnrollment, UserTotp};
+pub use user::{CreateUserDto, UpdateUserDto, User, UserQuery};
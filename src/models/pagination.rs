@@ -0,0 +1,195 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Decimal;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::configs::pagination::PaginationConfig;
+use crate::errors::AppError;
+
+/// One page of a keyset-paginated listing
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Pass as `cursor` to fetch the next page
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+/// A column that listings may be sorted by, with the SQL type of its cursor value
+pub struct SortColumn {
+    pub name: &'static str,
+    pub sql_type: SqlType,
+}
+
+/// Column types a cursor value can be parsed as
+#[derive(Debug, Clone, Copy)]
+pub enum SqlType {
+    Timestamptz,
+    Text,
+    Numeric,
+    Integer,
+}
+
+/// A cursor's sort value, parsed so it is bound with the column's own type
+#[derive(Debug, Clone)]
+pub enum CursorValue {
+    Timestamptz(DateTime<Utc>),
+    Text(String),
+    Numeric(Decimal),
+    Integer(i32),
+}
+
+impl SqlType {
+    /// `None` when a tampered cursor carries a value the column cannot hold
+    fn parse(self, value: String) -> Option<CursorValue> {
+        match self {
+            SqlType::Timestamptz => DateTime::parse_from_rfc3339(&value)
+                .ok()
+                .map(|t| CursorValue::Timestamptz(t.with_timezone(&Utc))),
+            SqlType::Text => Some(CursorValue::Text(value)),
+            SqlType::Numeric => value.parse().ok().map(CursorValue::Numeric),
+            SqlType::Integer => value.parse().ok().map(CursorValue::Integer),
+        }
+    }
+}
+
+/// Sort order parsed from `sort=column` (ascending) or `sort=-column` (descending)
+pub struct SortOrder {
+    pub column: &'static SortColumn,
+    pub descending: bool,
+}
+
+impl SortOrder {
+    fn parse(
+        sort: Option<&str>,
+        allowed: &'static [SortColumn],
+        default: &str,
+    ) -> Result<Self, AppError> {
+        let sort = sort.unwrap_or(default);
+        let (name, descending) = match sort.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (sort, false),
+        };
+
+        let column = allowed.iter().find(|c| c.name == name).ok_or_else(|| {
+            let names: Vec<&str> = allowed.iter().map(|c| c.name).collect();
+            AppError::BadRequest(format!(
+                "Cannot sort by '{}'; allowed columns: {}",
+                name,
+                names.join(", ")
+            ))
+        })?;
+
+        Ok(Self { column, descending })
+    }
+
+    /// Canonical `sort` parameter, used to tie cursors to the order they came from
+    fn key(&self) -> String {
+        if self.descending {
+            format!("-{}", self.column.name)
+        } else {
+            self.column.name.to_string()
+        }
+    }
+}
+
+/// Position after the last row of a page: its sort value and id
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    sort: String,
+    value: String,
+    id: Uuid,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("cursor serializes");
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        let json = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&json).ok()
+    }
+}
+
+/// Rows that can be positioned in a keyset-paginated listing
+pub trait Keyset {
+    fn id(&self) -> Uuid;
+
+    /// Value of a sort column, formatted so `SqlType` parses it back losslessly
+    fn sort_value(&self, column: &str) -> String;
+}
+
+/// Validated pagination parameters for one listing request
+pub struct PageRequest {
+    pub limit: i64,
+    pub sort: SortOrder,
+    pub after: Option<(CursorValue, Uuid)>,
+}
+
+impl PageRequest {
+    pub fn new(
+        config: &PaginationConfig,
+        limit: Option<i64>,
+        cursor: Option<&str>,
+        sort: Option<&str>,
+        allowed: &'static [SortColumn],
+        default_sort: &str,
+    ) -> Result<Self, AppError> {
+        let sort = SortOrder::parse(sort, allowed, default_sort)?;
+
+        let after = match cursor {
+            Some(cursor) => {
+                let cursor = Cursor::decode(cursor)
+                    .ok_or_else(|| AppError::BadRequest("Invalid cursor".to_string()))?;
+                if cursor.sort != sort.key() {
+                    return Err(AppError::BadRequest(
+                        "Cursor was issued for a different sort order".to_string(),
+                    ));
+                }
+                let value = sort
+                    .column
+                    .sql_type
+                    .parse(cursor.value)
+                    .ok_or_else(|| AppError::BadRequest("Invalid cursor".to_string()))?;
+                Some((value, cursor.id))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            limit: config.page_size(limit),
+            sort,
+            after,
+        })
+    }
+
+    /// Build a page from rows fetched with `limit + 1`; the extra row only signals `has_more`
+    pub fn into_page<T: Keyset>(self, mut rows: Vec<T>) -> Page<T> {
+        let has_more = rows.len() as i64 > self.limit;
+        rows.truncate(self.limit as usize);
+
+        let next_cursor = if has_more {
+            rows.last().map(|last| {
+                Cursor {
+                    sort: self.sort.key(),
+                    value: last.sort_value(self.sort.column.name),
+                    id: last.id(),
+                }
+                .encode()
+            })
+        } else {
+            None
+        };
+
+        Page {
+            items: rows,
+            next_cursor,
+            has_more,
+        }
+    }
+}
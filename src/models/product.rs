@@ -1,12 +1,17 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
+use validator::Validate;
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+use crate::models::pagination::Keyset;
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct Product {
     pub id: Uuid,
     pub name: String,
     pub description: Option<String>,
+    #[schema(value_type = String, example = "19.99")]
     pub price: sqlx::types::Decimal,
     pub stock: i32,
     pub created_by: Option<Uuid>,
@@ -14,26 +19,68 @@ pub struct Product {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+impl Keyset for Product {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn sort_value(&self, column: &str) -> String {
+        match column {
+            "name" => self.name.clone(),
+            "price" => self.price.to_string(),
+            "stock" => self.stock.to_string(),
+            _ => self.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+        }
+    }
+}
+
+/// Largest value that fits the `DECIMAL(10, 2)` price column
+const MAX_PRICE: f64 = 99_999_999.99;
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateProductDto {
+    #[validate(length(min = 1, max = 255))]
+    #[schema(min_length = 1, max_length = 255, example = "Mechanical Keyboard")]
     pub name: String,
+    #[validate(length(max = 2000))]
+    #[schema(max_length = 2000)]
     pub description: Option<String>,
+    #[validate(range(min = 0.0, max = MAX_PRICE))]
+    #[schema(minimum = 0.0, maximum = 99_999_999.99, example = 89.99)]
     pub price: f64,
+    #[validate(range(min = 0))]
+    #[schema(minimum = 0, example = 25)]
     pub stock: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateProductDto {
+    #[validate(length(min = 1, max = 255))]
+    #[schema(min_length = 1, max_length = 255)]
     pub name: Option<String>,
+    #[validate(length(max = 2000))]
+    #[schema(max_length = 2000)]
     pub description: Option<String>,
+    #[validate(range(min = 0.0, max = MAX_PRICE))]
+    #[schema(minimum = 0.0, maximum = 99_999_999.99)]
     pub price: Option<f64>,
+    #[validate(range(min = 0))]
+    #[schema(minimum = 0)]
     pub stock: Option<i32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ProductQuery {
+    /// Case-insensitive substring of the product name
     pub name: Option<String>,
     pub min_price: Option<f64>,
     pub max_price: Option<f64>,
     pub min_stock: Option<i32>,
+    /// Page size, capped at the server maximum
+    pub limit: Option<i64>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+    /// `name`, `price`, `stock` or `created_at`; prefix with `-` for descending
+    pub sort: Option<String>,
 }
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// A signed-in device: one login and every token refreshed from it
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Session {
+    /// Session id, also the `family_id` claim of its access tokens
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    /// Last authenticated request or token refresh
+    pub last_seen: DateTime<Utc>,
+    /// Client address at the last login or refresh
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// Whether this is the session making the request
+    pub current: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RevokeSessionsQuery {
+    /// Keep the session making the request signed in
+    #[serde(default)]
+    pub except_current: bool,
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A user's TOTP secret; `enabled_at` is unset until the first code is confirmed
+#[derive(Debug, sqlx::FromRow)]
+pub struct UserTotp {
+    pub user_id: Uuid,
+    pub secret: Vec<u8>,
+    pub enabled_at: Option<DateTime<Utc>>,
+}
+
+impl UserTotp {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled_at.is_some()
+    }
+}
+
+/// Secret to load into an authenticator app
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TotpEnrollment {
+    /// Base32 secret for manual entry
+    #[schema(example = "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP")]
+    pub secret: String,
+    /// `otpauth://` URI, usually rendered as a QR code
+    #[schema(
+        example = "otpauth://totp/Rust%20REST%20API:admin?secret=JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP&issuer=Rust%20REST%20API&algorithm=SHA1&digits=6&period=30"
+    )]
+    pub provisioning_uri: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TotpCodeDto {
+    /// Current authenticator code; disabling also accepts a recovery code
+    #[schema(example = "123456")]
+    pub code: String,
+}
+
+/// Shown once, when 2FA is enabled; each code signs in a single time
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecoveryCodes {
+    #[schema(example = json!(["k7qpm-x3rtz", "a2bcd-efg4h"]))]
+    pub recovery_codes: Vec<String>,
+}
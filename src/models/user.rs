@@ -1,28 +1,85 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
+use validator::Validate;
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+use crate::models::pagination::Keyset;
+use crate::validation::{validate_password_strength, validate_username};
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct User {
     pub id: Uuid,
     pub username: String,
     pub email: String,
     #[serde(skip_serializing)]
+    #[schema(ignore)]
     pub password_hash: String,
+    /// Null until the current email is confirmed
+    pub email_verified_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+impl Keyset for User {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn sort_value(&self, column: &str) -> String {
+        match column {
+            "username" => self.username.clone(),
+            "email" => self.email.clone(),
+            _ => self.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateUserDto {
+    #[validate(length(min = 3, max = 100), custom(function = "validate_username"))]
+    #[schema(
+        min_length = 3,
+        max_length = 100,
+        pattern = "^[A-Za-z0-9_.-]+$",
+        example = "jdoe"
+    )]
     pub username: String,
+    #[validate(email, length(max = 255))]
+    #[schema(format = Email, max_length = 255, example = "jdoe@example.com")]
     pub email: String,
+    // bcrypt only hashes the first 72 bytes
+    #[validate(
+        length(min = 8, max = 72),
+        custom(function = "validate_password_strength")
+    )]
+    #[schema(format = Password, min_length = 8, max_length = 72)]
     pub password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateUserDto {
+    #[validate(length(min = 3, max = 100), custom(function = "validate_username"))]
+    #[schema(min_length = 3, max_length = 100, pattern = "^[A-Za-z0-9_.-]+$")]
     pub username: Option<String>,
+    #[validate(email, length(max = 255))]
+    #[schema(format = Email, max_length = 255)]
     pub email: Option<String>,
+    #[validate(
+        length(min = 8, max = 72),
+        custom(function = "validate_password_strength")
+    )]
+    #[schema(format = Password, min_length = 8, max_length = 72)]
     pub password: Option<String>,
 }
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UserQuery {
+    /// Page size, capped at the server maximum
+    pub limit: Option<i64>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+    /// `username`, `email` or `created_at`; prefix with `-` for descending
+    pub sort: Option<String>,
+}
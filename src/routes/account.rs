@@ -0,0 +1,25 @@
+use crate::controllers;
+use actix_web::web;
+
+/// Self-service endpoints acting on the caller's own account
+pub fn configure_account_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/account")
+            .route(
+                "/2fa/enroll",
+                web::post().to(controllers::account::enroll_totp),
+            )
+            .route(
+                "/2fa/confirm",
+                web::post().to(controllers::account::confirm_totp),
+            )
+            .route(
+                "/2fa/disable",
+                web::post().to(controllers::account::disable_totp),
+            )
+            .route(
+                "/email/verification",
+                web::post().to(controllers::account::request_email_verification),
+            ),
+    );
+}
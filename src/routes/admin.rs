@@ -0,0 +1,57 @@
+use crate::controllers;
+use crate::middleware::RequirePermission;
+use actix_web::web;
+
+pub fn configure_admin_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/admin")
+            .route(
+                "/roles",
+                web::get()
+                    .to(controllers::role::get_all_roles)
+                    .wrap(RequirePermission::new("roles:read")),
+            )
+            .route(
+                "/users/{id}/roles",
+                web::get()
+                    .to(controllers::role::get_user_roles)
+                    .wrap(RequirePermission::new("roles:read")),
+            )
+            .route(
+                "/users/{id}/roles",
+                web::post()
+                    .to(controllers::role::assign_role)
+                    .wrap(RequirePermission::new("roles:write")),
+            )
+            .route(
+                "/users/{id}/roles/{role}",
+                web::delete()
+                    .to(controllers::role::revoke_role)
+                    .wrap(RequirePermission::new("roles:write")),
+            )
+            .route(
+                "/users/{id}/sessions",
+                web::get()
+                    .to(controllers::session::list_user_sessions)
+                    .wrap(RequirePermission::new("sessions:read")),
+            )
+            .route(
+                "/users/{id}/sessions",
+                web::delete()
+                    .to(controllers::session::revoke_user_sessions)
+                    .wrap(RequirePermission::new("users:write")),
+            )
+            .route(
+                "/lockouts/users/{username}",
+                web::delete()
+                    .to(controllers::lockout::unlock_user)
+                    .wrap(RequirePermission::new("users:write")),
+            )
+            .route(
+                "/lockouts/ips/{ip}",
+                web::delete()
+                    .to(controllers::lockout::unlock_ip)
+                    .wrap(RequirePermission::new("users:write")),
+            ),
+    );
+}
@@ -1,10 +1,39 @@
 use crate::controllers;
+use crate::middleware::AuthMiddleware;
 use actix_web::web;
 
 pub fn configure_auth_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/auth")
             .route("/login", web::post().to(controllers::auth::login))
-            .route("/logout", web::post().to(controllers::auth::logout)),
+            .route("/login/mfa", web::post().to(controllers::auth::login_mfa))
+            .route("/refresh", web::post().to(controllers::auth::refresh))
+            .route("/logout", web::post().to(controllers::auth::logout))
+            .route(
+                "/password/forgot",
+                web::post().to(controllers::auth::forgot_password),
+            )
+            .route(
+                "/password/reset",
+                web::post().to(controllers::auth::reset_password),
+            )
+            .route(
+                "/email/verify",
+                web::post().to(controllers::auth::verify_email),
+            )
+            // The only authenticated routes under /auth
+            .service(
+                web::scope("/sessions")
+                    .wrap(AuthMiddleware)
+                    .route("", web::get().to(controllers::session::list_sessions))
+                    .route(
+                        "",
+                        web::delete().to(controllers::session::revoke_all_sessions),
+                    )
+                    .route(
+                        "/{id}",
+                        web::delete().to(controllers::session::revoke_session),
+                    ),
+            ),
     );
 }
@@ -1,5 +1,175 @@
 use actix_web::{HttpResponse, web};
 use serde_json::json;
+use std::sync::LazyLock;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::{ContentBuilder, Ref, RefOr, ResponseBuilder};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::controllers;
+use crate::errors::ProblemDetails;
+use crate::middleware::is_public_path;
+use crate::models::health::Readiness;
+use crate::validation::FieldError;
+
+/// OpenAPI document generated from the handler and model annotations
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Rust REST API",
+        description = "User, product and role management backed by PostgreSQL and Redis. \
+            Errors are returned as RFC 7807 `application/problem+json`."
+    ),
+    paths(
+        controllers::health::livez,
+        controllers::health::readyz,
+        controllers::health::startupz,
+        super::metrics,
+        controllers::auth::login,
+        controllers::auth::refresh,
+        controllers::auth::login_mfa,
+        controllers::auth::logout,
+        controllers::auth::forgot_password,
+        controllers::auth::reset_password,
+        controllers::auth::verify_email,
+        controllers::auth::jwks,
+        controllers::session::list_sessions,
+        controllers::session::revoke_session,
+        controllers::session::revoke_all_sessions,
+        controllers::account::enroll_totp,
+        controllers::account::confirm_totp,
+        controllers::account::disable_totp,
+        controllers::account::request_email_verification,
+        controllers::user::create_user,
+        controllers::user::get_all_users,
+        controllers::user::get_user,
+        controllers::user::update_user,
+        controllers::user::delete_user,
+        controllers::session::list_user_sessions,
+        controllers::session::revoke_user_sessions,
+        controllers::lockout::unlock_user,
+        controllers::lockout::unlock_ip,
+        controllers::product::create_product,
+        controllers::product::search_products,
+        controllers::product::get_product,
+        controllers::product::update_product,
+        controllers::product::delete_product,
+        controllers::role::get_all_roles,
+        controllers::role::get_user_roles,
+        controllers::role::assign_role,
+        controllers::role::revoke_role,
+    ),
+    components(schemas(ProblemDetails, FieldError, Readiness)),
+    modifiers(&SecurityAddon, &CommonResponses),
+    tags(
+        (name = "Authentication", description = "Login, token lifecycle and signing keys, sessions, password reset and email verification"),
+        (name = "Account", description = "The caller's own account, email verification and two-factor authentication"),
+        (name = "Users", description = "User management"),
+        (name = "Products", description = "Product catalogue"),
+        (name = "Roles", description = "Role-based access control administration"),
+        (name = "System", description = "Probes and metrics"),
+    )
+)]
+pub struct ApiDoc;
+
+static OPENAPI: LazyLock<utoipa::openapi::OpenApi> = LazyLock::new(|| {
+    let mut openapi = ApiDoc::openapi();
+    // The crate declares no license; don't publish an empty one
+    openapi.info.license = None;
+    openapi
+});
+
+/// Registers the `bearer_auth` scheme referenced by protected operations
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Adds the error responses every operation can produce, so handlers only
+/// document the ones specific to them. Public auth endpoints sit outside
+/// `AuthMiddleware` and report their own 401s.
+struct CommonResponses;
+
+impl CommonResponses {
+    const ALL: [(&'static str, &'static str, &'static str); 2] = [
+        (
+            "429",
+            "TooManyRequests",
+            "Rate limit exceeded; see `Retry-After`",
+        ),
+        ("500", "InternalError", "Unexpected server error"),
+    ];
+    const PROTECTED: [(&'static str, &'static str, &'static str); 2] = [
+        (
+            "401",
+            "Unauthorized",
+            "Missing, invalid or revoked bearer token",
+        ),
+        ("403", "Forbidden", "Token lacks the required permission"),
+    ];
+}
+
+impl Modify for CommonResponses {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        for (_, name, description) in Self::ALL.iter().chain(Self::PROTECTED.iter()) {
+            components.responses.insert(
+                name.to_string(),
+                RefOr::T(
+                    ResponseBuilder::new()
+                        .description(*description)
+                        .content(
+                            "application/problem+json",
+                            ContentBuilder::new()
+                                .schema(Some(Ref::from_schema_name("ProblemDetails")))
+                                .build(),
+                        )
+                        .build(),
+                ),
+            );
+        }
+
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.post,
+                &mut item.put,
+                &mut item.delete,
+            ];
+            for operation in operations.into_iter().flatten() {
+                let protected = operation.security.is_some() && !is_public_path(path);
+                let common = Self::ALL
+                    .iter()
+                    .chain(Self::PROTECTED.iter().filter(|_| protected));
+
+                for (status, name, _) in common {
+                    operation
+                        .responses
+                        .responses
+                        .entry(status.to_string())
+                        .or_insert_with(|| RefOr::Ref(Ref::from_response_name(*name)));
+                }
+            }
+        }
+    }
+}
+
+/// Swagger UI at `/api/docs/ui/`, served from assets embedded in the binary
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new("/api/docs/ui/{_:.*}").url("/api/docs/openapi.json", OPENAPI.clone())
+}
 
 pub fn configure_docs_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -10,45 +180,30 @@ pub fn configure_docs_routes(cfg: &mut web::ServiceConfig) {
 }
 
 async fn get_api_docs() -> HttpResponse {
-    HttpResponse::Ok().json(json!({
-        "openapi": "3.0.0",
-        "info": {
-            "title": "Rust REST API",
-            "version": "1.0.0",
-            "description": "API documentation"
-        },
-        "paths": {
-            "/api/auth/login": {
-                "post": {
-                    "summary": "Login",
-                    "tags": ["Authentication"]
-                }
-            }
-        }
-    }))
+    HttpResponse::Ok().json(&*OPENAPI)
 }
 
 async fn list_endpoints() -> HttpResponse {
-    HttpResponse::Ok().json(json!({
-        "endpoints": [
-            {
-                "path": "/api/auth/login",
-                "method": "POST",
-                "protected": false,
-                "description": "User login"
-            },
-            {
-                "path": "/api/users",
-                "method": "GET",
-                "protected": true,
-                "description": "Get all users"
-            },
-            {
-                "path": "/api/products",
-                "method": "GET",
-                "protected": true,
-                "description": "Search products"
+    let mut endpoints = Vec::new();
+
+    for (path, item) in &OPENAPI.paths.paths {
+        let operations = [
+            ("GET", &item.get),
+            ("POST", &item.post),
+            ("PUT", &item.put),
+            ("DELETE", &item.delete),
+        ];
+        for (method, operation) in operations {
+            if let Some(operation) = operation {
+                endpoints.push(json!({
+                    "path": path,
+                    "method": method,
+                    "protected": operation.security.is_some(),
+                    "description": operation.summary,
+                }));
             }
-        ]
-    }))
+        }
+    }
+
+    HttpResponse::Ok().json(json!({ "endpoints": endpoints }))
 }
@@ -0,0 +1,9 @@
+use crate::controllers;
+use actix_web::web;
+
+/// Orchestrator probes, served outside `/api` so they bypass auth
+pub fn configure_health_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/livez", web::get().to(controllers::health::livez))
+        .route("/readyz", web::get().to(controllers::health::readyz))
+        .route("/startupz", web::get().to(controllers::health::startupz));
+}
@@ -1,46 +1,69 @@
 use crate::configs::AppState;
+use crate::controllers;
+use crate::metrics::METRICS;
 use crate::middleware::AuthMiddleware;
 use actix_web::HttpResponse;
 use actix_web::web;
 
+mod account;
+mod admin;
 mod auth;
 mod docs;
+mod health;
 mod product;
 mod user;
 
+pub use account::configure_account_routes;
+pub use admin::configure_admin_routes;
 pub use auth::configure_auth_routes;
-pub use docs::configure_docs_routes;
+pub use docs::{configure_docs_routes, swagger_ui};
+pub use health::configure_health_routes;
 pub use product::configure_product_routes;
 pub use user::configure_user_routes;
 
-// Health check endpoint
-async fn health_check(state: web::Data<AppState>) -> HttpResponse {
-    match state.health_check().await {
-        Ok(status) => {
-            let http_status = if status.status == "healthy" {
-                actix_web::http::StatusCode::OK
-            } else {
-                actix_web::http::StatusCode::SERVICE_UNAVAILABLE
-            };
-            HttpResponse::build(http_status).json(status)
+/// Prometheus scrape endpoint
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "System",
+    responses(
+        (status = 200, description = "Metrics in Prometheus text format", body = String,
+            content_type = "text/plain; version=0.0.4"),
+    )
+)]
+async fn metrics(state: web::Data<AppState>) -> HttpResponse {
+    match METRICS.render(&state) {
+        Ok(body) => HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4; charset=utf-8")
+            .body(body),
+        Err(e) => {
+            tracing::error!("Failed to encode metrics: {}", e);
+            HttpResponse::InternalServerError().finish()
         }
-        Err(e) => HttpResponse::ServiceUnavailable().json(serde_json::json!({
-            "status": "error",
-            "message": format!("Health check failed: {}", e)
-        })),
     }
 }
 
 // Main router configuration
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/metrics", web::get().to(metrics));
+    cfg.route(
+        "/.well-known/jwks.json",
+        web::get().to(controllers::auth::jwks),
+    );
+    cfg.configure(configure_health_routes);
+    // Registered before the /api scope so its resources match first
+    cfg.service(swagger_ui());
     cfg.service(
         web::scope("/api")
-            .route("/health", web::get().to(health_check))
+            // Kept for existing clients; same report as /readyz
+            .route("/health", web::get().to(controllers::health::readyz))
             .configure(configure_auth_routes)
             .configure(configure_docs_routes)
             .service(
                 web::scope("")
                     .wrap(AuthMiddleware)
+                    .configure(configure_account_routes)
+                    .configure(configure_admin_routes)
                     .configure(configure_user_routes)
                     .configure(configure_product_routes),
             ),
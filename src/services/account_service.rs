@@ -0,0 +1,224 @@
+use bcrypt::{DEFAULT_COST, hash};
+use redis::AsyncCommands;
+use sha2::{Digest, Sha256};
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+use crate::configs::AppState;
+use crate::dao::UserDao;
+use crate::errors::AppError;
+use crate::mail::Email;
+use crate::metrics::time_redis;
+use crate::models::{EmailVerificationData, User};
+use crate::services::SessionService;
+
+/// Replace a user's reset token: drop the previous one, if any, and store the
+/// new one under both keys. One round trip, like the lookup for unknown emails.
+static ROTATE_RESET_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r"
+        local previous = redis.call('GET', KEYS[1])
+        if previous then
+            redis.call('DEL', 'password_reset:' .. previous)
+        end
+        redis.call('SET', KEYS[2], ARGV[1], 'EX', ARGV[3])
+        redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+        return 1
+        ",
+    )
+});
+
+/// Password reset and email verification.
+///
+/// Tokens are random, single-use and kept in Redis only as SHA-256 hashes, so a
+/// leaked Redis dump cannot be replayed as reset links.
+pub struct AccountService;
+
+impl AccountService {
+    /// Email a reset link if `email` belongs to a user. Unknown addresses are
+    /// not reported, and cost the same database query and Redis round trip as
+    /// known ones, so neither the response nor its timing reveals accounts.
+    pub async fn forgot_password(state: &AppState, email: &str) -> Result<(), AppError> {
+        let user = UserDao::find_by_email(&state.db, email).await?;
+        let mut redis_conn = state.redis.clone();
+
+        let Some(user) = user else {
+            let _: Option<String> = time_redis(
+                "GET",
+                redis_conn.get(format!("password_reset_user:{}", Uuid::new_v4())),
+            )
+            .await?;
+            tracing::info!("Password reset requested for an unknown email");
+            return Ok(());
+        };
+
+        let token = new_token();
+        let ttl = state.auth.password_reset_ttl_secs as u64;
+
+        // Only the newest link works; requesting another invalidates the previous one
+        let _: i64 = time_redis(
+            "EVALSHA",
+            ROTATE_RESET_SCRIPT
+                .key(format!("password_reset_user:{}", user.id))
+                .key(format!("password_reset:{}", token_hash(&token)))
+                .arg(user.id.to_string())
+                .arg(token_hash(&token))
+                .arg(ttl)
+                .invoke_async(&mut redis_conn),
+        )
+        .await?;
+
+        let link = state.mail.link("reset-password", &token);
+        send(
+            state,
+            Email {
+                to: user.email.clone(),
+                subject: "Reset your password".to_string(),
+                body: format!(
+                    "Hello {},\n\nUse this link to choose a new password:\n\n{}\n\n\
+                     It expires in {} minutes and works once. If you did not ask for \
+                     a reset, ignore this email.\n",
+                    user.username,
+                    link,
+                    ttl / 60
+                ),
+            },
+        );
+
+        tracing::info!(user_id = %user.id, "Password reset link sent");
+        Ok(())
+    }
+
+    /// Set a new password with a reset token and sign the user out everywhere
+    pub async fn reset_password(
+        state: &AppState,
+        token: &str,
+        new_password: &str,
+    ) -> Result<(), AppError> {
+        let hashed = token_hash(token);
+
+        // GETDEL makes the token single-use even under concurrent requests
+        let mut redis_conn = state.redis.clone();
+        let user_id: Option<String> = time_redis(
+            "GETDEL",
+            redis_conn.get_del(format!("password_reset:{}", hashed)),
+        )
+        .await?;
+        let user_id = user_id
+            .and_then(|id| Uuid::parse_str(&id).ok())
+            .ok_or_else(|| AppError::BadRequest("Invalid or expired reset token".to_string()))?;
+        let _: () = time_redis(
+            "DEL",
+            redis_conn.del(format!("password_reset_user:{}", user_id)),
+        )
+        .await?;
+
+        let password_hash = hash(new_password, DEFAULT_COST)
+            .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))?;
+        let result = UserDao::update_password(&state.db, user_id, &password_hash).await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::BadRequest(
+                "Invalid or expired reset token".to_string(),
+            ));
+        }
+
+        // Whoever knew the old password may hold a session
+        SessionService::revoke_all(state, user_id, None).await?;
+
+        tracing::info!(user_id = %user_id, "Password reset completed");
+        Ok(())
+    }
+
+    /// Email a verification link for the user's current address
+    pub async fn send_verification(state: &AppState, user: &User) -> Result<(), AppError> {
+        if user.email_verified_at.is_some() {
+            return Err(AppError::Conflict("Email is already verified".to_string()));
+        }
+
+        let token = new_token();
+        let data = serde_json::to_string(&EmailVerificationData {
+            user_id: user.id.to_string(),
+            email: user.email.clone(),
+        })
+        .map_err(|e| AppError::Internal(format!("Failed to create verification token: {}", e)))?;
+
+        let ttl = state.auth.email_verification_ttl_secs as u64;
+        let mut redis_conn = state.redis.clone();
+        let _: () = time_redis(
+            "SETEX",
+            redis_conn.set_ex(
+                format!("email_verification:{}", token_hash(&token)),
+                data,
+                ttl,
+            ),
+        )
+        .await?;
+
+        let link = state.mail.link("verify-email", &token);
+        send(
+            state,
+            Email {
+                to: user.email.clone(),
+                subject: "Confirm your email address".to_string(),
+                body: format!(
+                    "Hello {},\n\nConfirm that this is your email address:\n\n{}\n\n\
+                     The link expires in {} hours and works once.\n",
+                    user.username,
+                    link,
+                    ttl / 3600
+                ),
+            },
+        );
+
+        tracing::info!(user_id = %user.id, "Email verification link sent");
+        Ok(())
+    }
+
+    /// Mark the email a verification token was issued for as verified
+    pub async fn verify_email(state: &AppState, token: &str) -> Result<(), AppError> {
+        let mut redis_conn = state.redis.clone();
+        let data: Option<String> = time_redis(
+            "GETDEL",
+            redis_conn.get_del(format!("email_verification:{}", token_hash(token))),
+        )
+        .await?;
+
+        let invalid = || AppError::BadRequest("Invalid or expired verification token".to_string());
+        let data: EmailVerificationData = data
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .ok_or_else(invalid)?;
+        let user_id = Uuid::parse_str(&data.user_id).map_err(|_| invalid())?;
+
+        let result = UserDao::mark_email_verified(&state.db, user_id, &data.email).await?;
+        if result.rows_affected() == 0 {
+            // The user was deleted or changed their email after the link was sent
+            return Err(invalid());
+        }
+
+        tracing::info!(user_id = %user_id, "Email verified");
+        Ok(())
+    }
+}
+
+/// Deliver in the background so responses do not wait on the mail server
+fn send(state: &AppState, email: Email) {
+    let mailer = state.mailer.clone();
+    state.shutdown.spawn("mail", async move {
+        if let Err(e) = mailer.send(email).await {
+            tracing::error!(error = %e, "Failed to send email");
+        }
+    });
+}
+
+/// 244 random bits, the same shape as refresh tokens
+fn new_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Tokens are random, so a fast unsalted hash is enough
+fn token_hash(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
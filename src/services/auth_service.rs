@@ -1,81 +1,436 @@
-use bcrypt::verify;
+use bcrypt::{DEFAULT_COST, hash, verify};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use redis::AsyncCommands;
+use std::sync::LazyLock;
+use uuid::Uuid;
 
 use crate::configs::AppState;
 use crate::dao::UserDao;
-use crate::models::{Claims, LoginDto, TokenResponse};
+use crate::errors::AppError;
+use crate::metrics::{METRICS, time_redis};
+use crate::middleware::ClientInfo;
+use crate::models::{
+    Claims, LoginDto, LoginResponse, MfaChallenge, MfaChallengeData, MfaLoginDto, RefreshTokenData,
+    TokenResponse,
+};
+use crate::services::{LockoutService, RoleService, SessionService, TotpService};
+
+/// Wrong second factors allowed per challenge before it is revoked
+const MAX_MFA_ATTEMPTS: i64 = 5;
+
+/// Checked against when the username does not exist, so an unknown username
+/// costs the same bcrypt work as a wrong password
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+    hash(Uuid::new_v4().to_string(), DEFAULT_COST).expect("bcrypt hashes a fixed-size input")
+});
+
+/// Consume a refresh token and leave a reuse marker holding its family, in one
+/// step so a replay can never fall between the two. Returns `{1, data}` for a
+/// live token, `{0, family_id}` for a used one, and nil otherwise.
+static CONSUME_REFRESH_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r"
+        local data = redis.call('GET', KEYS[1])
+        if data then
+            redis.call('DEL', KEYS[1])
+            local family_id = cjson.decode(data)['family_id']
+            redis.call('SET', KEYS[2], family_id, 'EX', ARGV[1])
+            return {1, data}
+        end
+        local family_id = redis.call('GET', KEYS[2])
+        if family_id then
+            return {0, family_id}
+        end
+        return false
+        ",
+    )
+});
 
 pub struct AuthService;
 
 impl AuthService {
-    pub async fn login(state: &AppState, dto: LoginDto) -> Result<TokenResponse, String> {
-        let user = UserDao::find_by_username(&state.db, &dto.username)
-            .await
-            .map_err(|_| "Invalid credentials")?;
+    /// Password login, throttled per username and per client IP
+    pub async fn login(
+        state: &AppState,
+        dto: LoginDto,
+        client: &ClientInfo,
+    ) -> Result<LoginResponse, AppError> {
+        let username = dto.username.clone();
+
+        if let Some(retry_after) = LockoutService::retry_after(state, &username, client.ip).await? {
+            record_login("throttled");
+            return Err(AppError::TooManyRequests(
+                "Too many failed login attempts. Please try again later.".to_string(),
+                Some(retry_after),
+            ));
+        }
 
-        let valid =
-            verify(&dto.password, &user.password_hash).map_err(|_| "Invalid credentials")?;
+        let result = Self::authenticate(state, dto, client).await;
 
-        if !valid {
-            return Err("Invalid credentials".to_string());
+        // A correct password alone clears nothing: with 2FA the failures are
+        // only cleared once the second factor is also right
+        match &result {
+            Ok(LoginResponse::Tokens(_)) => {
+                LockoutService::record_success(state, &username).await?
+            }
+            Ok(LoginResponse::MfaRequired(_)) => {}
+            Err(AppError::Unauthorized(_)) => {
+                LockoutService::record_failure(state, &username, client.ip).await?
+            }
+            Err(_) => {}
         }
 
-        let expiration = Utc::now() + Duration::hours(24);
-        let claims = Claims {
-            sub: user.username.clone(),
-            user_id: user.id.to_string(),
-            exp: expiration.timestamp() as usize,
+        let outcome = match &result {
+            Ok(LoginResponse::Tokens(_)) => "success",
+            Ok(LoginResponse::MfaRequired(_)) => "mfa_required",
+            Err(AppError::Unauthorized(_)) => "failure",
+            Err(_) => "error",
+        };
+        record_login(outcome);
+
+        result
+    }
+
+    /// Second step of a 2FA login: trade a challenge and a code for tokens.
+    /// Wrong codes count against the same username and IP limits as wrong
+    /// passwords, so starting new challenges does not reset the budget.
+    pub async fn login_mfa(
+        state: &AppState,
+        dto: MfaLoginDto,
+        client: &ClientInfo,
+    ) -> Result<TokenResponse, AppError> {
+        let result = Self::complete_challenge(state, dto, client).await;
+
+        let outcome = match &result {
+            Ok(_) => "mfa_success",
+            Err(AppError::Unauthorized(_)) => "mfa_failure",
+            Err(AppError::TooManyRequests(..)) => "throttled",
+            Err(_) => "error",
+        };
+        record_login(outcome);
+
+        result
+    }
+
+    async fn authenticate(
+        state: &AppState,
+        dto: LoginDto,
+        client: &ClientInfo,
+    ) -> Result<LoginResponse, AppError> {
+        let user = match UserDao::find_by_username(&state.db, &dto.username).await {
+            Ok(user) => Some(user),
+            Err(sqlx::Error::RowNotFound) => None,
+            Err(e) => return Err(e.into()),
         };
 
-        let token = encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(state.jwt_secret.as_bytes()),
+        // Always run bcrypt so response time does not reveal whether the user exists
+        let password_hash = user
+            .as_ref()
+            .map_or(DUMMY_PASSWORD_HASH.as_str(), |u| u.password_hash.as_str());
+        let valid = verify(&dto.password, password_hash).unwrap_or(false);
+
+        let Some(user) = user.filter(|_| valid) else {
+            return Err(invalid_credentials());
+        };
+
+        if TotpService::is_enabled(state, user.id).await? {
+            let challenge = Self::create_challenge(state, &user.username, user.id).await?;
+            return Ok(LoginResponse::MfaRequired(challenge));
+        }
+
+        // Every login starts a new token family
+        let family_id = Uuid::new_v4().to_string();
+
+        Self::issue_tokens(
+            state,
+            &user.username,
+            &user.id.to_string(),
+            &family_id,
+            client,
         )
-        .map_err(|_| "Failed to generate token")?;
+        .await
+        .map(LoginResponse::Tokens)
+    }
+
+    /// Remember that `user_id` passed the password check, for `mfa_challenge_ttl_secs`
+    async fn create_challenge(
+        state: &AppState,
+        username: &str,
+        user_id: Uuid,
+    ) -> Result<MfaChallenge, AppError> {
+        let challenge_token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let data = serde_json::to_string(&MfaChallengeData {
+            user_id: user_id.to_string(),
+            username: username.to_string(),
+        })
+        .map_err(|e| AppError::Internal(format!("Failed to create MFA challenge: {}", e)))?;
 
+        let ttl = state.auth.mfa_challenge_ttl_secs;
         let mut redis_conn = state.redis.clone();
-        let _: () = redis_conn
-            .set_ex(format!("token:{}", token), user.id.to_string(), 86400)
-            .await
-            .map_err(|_| "Failed to store token")?;
+        let _: () = time_redis(
+            "SETEX",
+            redis_conn.set_ex(
+                format!("mfa_challenge:{}", challenge_token),
+                data,
+                ttl as u64,
+            ),
+        )
+        .await?;
 
-        Ok(TokenResponse {
-            token,
-            expires_in: 86400,
+        Ok(MfaChallenge {
+            mfa_required: true,
+            challenge_token,
+            expires_in: ttl,
         })
     }
 
-    pub async fn logout(state: &AppState, token: &str) -> Result<(), String> {
+    async fn complete_challenge(
+        state: &AppState,
+        dto: MfaLoginDto,
+        client: &ClientInfo,
+    ) -> Result<TokenResponse, AppError> {
+        let challenge_key = format!("mfa_challenge:{}", dto.challenge_token);
+        let attempts_key = format!("mfa_attempts:{}", dto.challenge_token);
         let mut redis_conn = state.redis.clone();
-        let _: () = redis_conn
-            .del(format!("token:{}", token))
-            .await
-            .map_err(|_| "Failed to invalidate token")?;
 
-        Ok(())
+        let data: Option<String> = time_redis("GET", redis_conn.get(&challenge_key)).await?;
+        let data: MfaChallengeData = data
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .ok_or_else(|| {
+                AppError::Unauthorized("Invalid or expired MFA challenge".to_string())
+            })?;
+        let user_id = Uuid::parse_str(&data.user_id)
+            .map_err(|_| AppError::Unauthorized("Invalid MFA challenge".to_string()))?;
+
+        if let Some(retry_after) =
+            LockoutService::retry_after(state, &data.username, client.ip).await?
+        {
+            return Err(AppError::TooManyRequests(
+                "Too many failed login attempts. Please try again later.".to_string(),
+                Some(retry_after),
+            ));
+        }
+
+        if !TotpService::verify_second_factor(state, user_id, &dto.code).await? {
+            LockoutService::record_failure(state, &data.username, client.ip).await?;
+            let attempts: i64 = time_redis("INCR", redis_conn.incr(&attempts_key, 1)).await?;
+            let _: () = time_redis(
+                "EXPIRE",
+                redis_conn.expire(&attempts_key, state.auth.mfa_challenge_ttl_secs),
+            )
+            .await?;
+
+            if attempts >= MAX_MFA_ATTEMPTS {
+                tracing::warn!(user_id = %user_id, "Too many wrong codes, MFA challenge revoked");
+                let _: () = time_redis(
+                    "DEL",
+                    redis_conn.del(&[challenge_key.as_str(), attempts_key.as_str()]),
+                )
+                .await?;
+            }
+            return Err(AppError::Unauthorized(
+                "Invalid verification code".to_string(),
+            ));
+        }
+
+        // The challenge is single-use; only the request that deletes it gets tokens
+        let deleted: i64 = time_redis("DEL", redis_conn.del(&challenge_key)).await?;
+        let _: () = time_redis("DEL", redis_conn.del(&attempts_key)).await?;
+        if deleted == 0 {
+            return Err(AppError::Unauthorized(
+                "Invalid or expired MFA challenge".to_string(),
+            ));
+        }
+
+        LockoutService::record_success(state, &data.username).await?;
+
+        let family_id = Uuid::new_v4().to_string();
+        Self::issue_tokens(state, &data.username, &data.user_id, &family_id, client).await
     }
 
-    pub async fn validate_token(state: &AppState, token: &str) -> Result<Claims, String> {
+    /// Exchange a refresh token for a new token pair.
+    ///
+    /// Refresh tokens are single-use: each call rotates the refresh token. Presenting
+    /// an already used refresh token revokes every token issued in its family.
+    pub async fn refresh(
+        state: &AppState,
+        refresh_token: &str,
+        client: &ClientInfo,
+    ) -> Result<TokenResponse, AppError> {
         let mut redis_conn = state.redis.clone();
-        let exists: bool = redis_conn
-            .exists(format!("token:{}", token))
-            .await
-            .map_err(|_| "Token validation failed")?;
 
-        if !exists {
-            return Err("Token expired or invalid".to_string());
-        }
+        let consumed: Option<(i64, String)> = time_redis(
+            "EVALSHA",
+            CONSUME_REFRESH_SCRIPT
+                .key(format!("refresh:{}", refresh_token))
+                .key(format!("refresh_used:{}", refresh_token))
+                .arg(state.auth.refresh_token_ttl_secs)
+                .invoke_async(&mut redis_conn),
+        )
+        .await?;
 
-        let token_data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(state.jwt_secret.as_bytes()),
-            &Validation::default(),
+        let data = match consumed {
+            Some((1, data)) => data,
+            Some((_, family_id)) => {
+                tracing::warn!(
+                    family_id = %family_id,
+                    "Refresh token reuse detected, revoking token family"
+                );
+                SessionService::revoke_family(state, &family_id).await?;
+                return Err(AppError::Unauthorized(
+                    "Invalid or expired refresh token".to_string(),
+                ));
+            }
+            None => {
+                return Err(AppError::Unauthorized(
+                    "Invalid or expired refresh token".to_string(),
+                ));
+            }
+        };
+
+        let data: RefreshTokenData = serde_json::from_str(&data)
+            .map_err(|_| AppError::Unauthorized("Invalid refresh token".to_string()))?;
+
+        Self::issue_tokens(
+            state,
+            &data.username,
+            &data.user_id,
+            &data.family_id,
+            client,
         )
-        .map_err(|_| "Invalid token")?;
+        .await
+    }
+
+    pub async fn logout(state: &AppState, token: &str) -> Result<(), AppError> {
+        let mut redis_conn = state.redis.clone();
+        let _: () = time_redis("DEL", redis_conn.del(format!("token:{}", token))).await?;
+
+        // Also drop the refresh token so the session cannot be resumed
+        if let Ok(claims) = Self::decode_token(state, token) {
+            SessionService::revoke_family(state, &claims.family_id).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn validate_token(state: &AppState, token: &str) -> Result<Claims, AppError> {
+        let claims = Self::decode_token(state, token)?;
+
+        if !SessionService::touch(state, token, &claims.family_id).await? {
+            return Err(AppError::Unauthorized(
+                "Token expired or invalid".to_string(),
+            ));
+        }
+
+        Ok(claims)
+    }
+
+    /// Verify a token's signature and expiry without checking that it is still live in Redis
+    pub fn decode_token(state: &AppState, token: &str) -> Result<Claims, AppError> {
+        state
+            .jwt
+            .decode::<Claims>(token)
+            .map_err(|_| AppError::Unauthorized("Invalid token".to_string()))
+    }
+
+    /// Issue an access token and a refresh token belonging to `family_id`, and
+    /// record the session it belongs to
+    async fn issue_tokens(
+        state: &AppState,
+        username: &str,
+        user_id: &str,
+        family_id: &str,
+        client: &ClientInfo,
+    ) -> Result<TokenResponse, AppError> {
+        // Roles are reloaded on every refresh so grants and revocations take effect
+        let user_uuid = Uuid::parse_str(user_id)
+            .map_err(|_| AppError::Unauthorized("Invalid token subject".to_string()))?;
+        let (roles, permissions) = RoleService::get_authorities(&state.db, user_uuid).await?;
 
-        Ok(token_data.claims)
+        let expiration = Utc::now() + Duration::seconds(state.auth.access_token_ttl_secs);
+        let claims = Claims {
+            sub: username.to_string(),
+            user_id: user_id.to_string(),
+            family_id: family_id.to_string(),
+            roles,
+            permissions,
+            exp: expiration.timestamp() as usize,
+        };
+
+        let token = state
+            .jwt
+            .encode(&claims)
+            .map_err(|e| AppError::Internal(format!("Failed to generate token: {}", e)))?;
+
+        let refresh_token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let refresh_data = serde_json::to_string(&RefreshTokenData {
+            user_id: user_id.to_string(),
+            username: username.to_string(),
+            family_id: family_id.to_string(),
+        })
+        .map_err(|e| AppError::Internal(format!("Failed to generate refresh token: {}", e)))?;
+
+        let token_key = format!("token:{}", token);
+        let refresh_key = format!("refresh:{}", refresh_token);
+        let family_key = format!("refresh_family:{}", family_id);
+        let user_families_key = format!("user_families:{}", user_id);
+        let session_key = format!("session:{}", family_id);
+        let now = Utc::now().timestamp();
+
+        let mut session_fields = vec![
+            ("user_id", user_id.to_string()),
+            ("last_seen", now.to_string()),
+        ];
+        if let Some(ip) = client.ip {
+            session_fields.push(("ip", ip.to_string()));
+        }
+        if let Some(user_agent) = &client.user_agent {
+            session_fields.push(("user_agent", user_agent.clone()));
+        }
+
+        let mut redis_conn = state.redis.clone();
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .set_ex(&token_key, user_id, state.auth.access_token_ttl_secs as u64)
+            .ignore()
+            .set_ex(
+                &refresh_key,
+                refresh_data,
+                state.auth.refresh_token_ttl_secs as u64,
+            )
+            .ignore()
+            .sadd(&family_key, &[&token_key, &refresh_key])
+            .ignore()
+            .expire(&family_key, state.auth.refresh_token_ttl_secs)
+            .ignore()
+            .sadd(&user_families_key, family_id)
+            .ignore()
+            .expire(&user_families_key, state.auth.refresh_token_ttl_secs)
+            .ignore()
+            .hset_nx(&session_key, "created_at", now)
+            .ignore()
+            .hset_multiple(&session_key, &session_fields)
+            .ignore()
+            .expire(&session_key, state.auth.refresh_token_ttl_secs)
+            .ignore();
+        time_redis("MULTI", pipe.query_async::<()>(&mut redis_conn)).await?;
+
+        Ok(TokenResponse {
+            token,
+            expires_in: state.auth.access_token_ttl_secs,
+            refresh_token,
+            refresh_expires_in: state.auth.refresh_token_ttl_secs,
+        })
     }
 }
+
+fn record_login(outcome: &str) {
+    METRICS
+        .login_attempts_total
+        .with_label_values(&[outcome])
+        .inc();
+}
+
+fn invalid_credentials() -> AppError {
+    AppError::Unauthorized("Invalid credentials".to_string())
+}
@@ -0,0 +1,100 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::configs::AppState;
+use crate::metrics::time_redis;
+use crate::models::health::{
+    CheckStatus, Checks, DependencyCheck, PoolStats, Readiness, ReadinessStatus,
+};
+
+pub struct HealthService;
+
+impl HealthService {
+    /// Probe PostgreSQL and Redis concurrently and combine the results
+    pub async fn readiness(state: &AppState) -> Readiness {
+        let config = &state.health;
+
+        let db_probe = async { sqlx::query("SELECT 1").execute(&state.db).await.map(|_| ()) };
+        let redis_probe = async {
+            let mut redis_conn = state.redis.clone();
+            time_redis(
+                "PING",
+                redis::cmd("PING").query_async::<()>(&mut redis_conn),
+            )
+            .await
+        };
+
+        let (mut database, redis) = tokio::join!(
+            probe(db_probe, config.check_timeout(), config.db_degraded_after()),
+            probe(
+                redis_probe,
+                config.check_timeout(),
+                config.redis_degraded_after()
+            ),
+        );
+
+        let pool = PoolStats {
+            size: state.db.size(),
+            idle: state.db.num_idle(),
+            max: state.db.options().get_max_connections(),
+        };
+        // Every connection checked out means new queries queue for one
+        if database.status == CheckStatus::Up && pool.idle == 0 && pool.size >= pool.max {
+            database.status = CheckStatus::Degraded;
+        }
+        database.pool = Some(pool);
+
+        let status = match (database.status, redis.status) {
+            _ if state.probes.is_draining() => ReadinessStatus::Draining,
+            (CheckStatus::Down, _) | (_, CheckStatus::Down) => ReadinessStatus::Unavailable,
+            (CheckStatus::Degraded, _) | (_, CheckStatus::Degraded) => ReadinessStatus::Degraded,
+            _ => ReadinessStatus::Ready,
+        };
+
+        if matches!(
+            status,
+            ReadinessStatus::Degraded | ReadinessStatus::Unavailable
+        ) {
+            tracing::warn!(
+                database = ?database.status,
+                redis = ?redis.status,
+                "Readiness check reported {:?}",
+                status
+            );
+        }
+
+        Readiness {
+            status,
+            checks: Checks { database, redis },
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Time one dependency round trip, bounded by `timeout`
+async fn probe<E: std::fmt::Display>(
+    check: impl Future<Output = Result<(), E>>,
+    timeout: Duration,
+    degraded_after: Duration,
+) -> DependencyCheck {
+    let start = Instant::now();
+    let result = tokio::time::timeout(timeout, check).await;
+    let elapsed = start.elapsed();
+
+    let (status, error) = match result {
+        Ok(Ok(())) if elapsed > degraded_after => (CheckStatus::Degraded, None),
+        Ok(Ok(())) => (CheckStatus::Up, None),
+        Ok(Err(e)) => (CheckStatus::Down, Some(e.to_string())),
+        Err(_) => (
+            CheckStatus::Down,
+            Some(format!("timed out after {}ms", timeout.as_millis())),
+        ),
+    };
+
+    DependencyCheck {
+        status,
+        latency_ms: elapsed.as_secs_f64() * 1000.0,
+        error,
+        pool: None,
+    }
+}
@@ -0,0 +1,211 @@
+use redis::AsyncCommands;
+use std::net::IpAddr;
+
+use crate::configs::AppState;
+use crate::errors::AppError;
+use crate::metrics::time_redis;
+
+/// Failed-login counters, backoff and lockouts, kept in Redis.
+///
+/// Keys are derived from the submitted username rather than a user id, so an
+/// unknown username is throttled exactly like a real one. Lockout and unlock
+/// events are logged with the `audit` target.
+pub struct LockoutService;
+
+impl LockoutService {
+    /// Seconds until `username` may try again from `ip`, or `None` if it may now
+    pub async fn retry_after(
+        state: &AppState,
+        username: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<Option<u64>, AppError> {
+        let mut keys = vec![user_lockout_key(username), backoff_key(username)];
+        if let Some(ip) = ip {
+            keys.push(ip_lockout_key(ip));
+        }
+
+        let mut pipe = redis::pipe();
+        for key in &keys {
+            pipe.pttl(key);
+        }
+        let mut redis_conn = state.redis.clone();
+        let ttls: Vec<i64> = time_redis("PTTL", pipe.query_async(&mut redis_conn)).await?;
+
+        // PTTL is negative for missing keys
+        let wait_ms = ttls.into_iter().max().unwrap_or(0);
+        Ok((wait_ms > 0).then(|| (wait_ms as u64).div_ceil(1000)))
+    }
+
+    /// Count a failed login and start backoff or a lockout when a threshold is crossed
+    pub async fn record_failure(
+        state: &AppState,
+        username: &str,
+        ip: Option<IpAddr>,
+    ) -> Result<(), AppError> {
+        let config = &state.lockout;
+        let window = config.failure_window_secs as i64;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .incr(user_failures_key(username), 1)
+            .expire(user_failures_key(username), window)
+            .ignore();
+        if let Some(ip) = ip {
+            pipe.incr(ip_failures_key(ip), 1)
+                .expire(ip_failures_key(ip), window)
+                .ignore();
+        }
+        let mut redis_conn = state.redis.clone();
+        let counts: Vec<u32> = time_redis("MULTI", pipe.query_async(&mut redis_conn)).await?;
+        let user_failures = counts.first().copied().unwrap_or(0);
+
+        tracing::info!(
+            target: "audit",
+            event = "login_failed",
+            username = %username,
+            ip = ip.map(tracing::field::display),
+            failures = user_failures,
+            "Login failed"
+        );
+
+        if user_failures >= config.max_failures_per_user {
+            let locked: Option<String> = time_redis(
+                "SET",
+                redis_conn.set_options(
+                    user_lockout_key(username),
+                    1,
+                    redis::SetOptions::default()
+                        .conditional_set(redis::ExistenceCheck::NX)
+                        .with_expiration(redis::SetExpiry::EX(config.lockout_secs)),
+                ),
+            )
+            .await?;
+            if locked.is_some() {
+                tracing::warn!(
+                    target: "audit",
+                    event = "account_locked",
+                    username = %username,
+                    ip = ip.map(tracing::field::display),
+                    failures = user_failures,
+                    lockout_secs = config.lockout_secs,
+                    "Username locked out after repeated failures"
+                );
+            }
+        } else if let Some(delay) = config.backoff(user_failures) {
+            let _: () = time_redis(
+                "SET",
+                redis_conn.pset_ex(backoff_key(username), 1, delay.as_millis() as u64),
+            )
+            .await?;
+        }
+
+        if let (Some(ip), Some(&ip_failures)) = (ip, counts.get(1))
+            && ip_failures >= config.max_failures_per_ip
+        {
+            let locked: Option<String> = time_redis(
+                "SET",
+                redis_conn.set_options(
+                    ip_lockout_key(ip),
+                    1,
+                    redis::SetOptions::default()
+                        .conditional_set(redis::ExistenceCheck::NX)
+                        .with_expiration(redis::SetExpiry::EX(config.lockout_secs)),
+                ),
+            )
+            .await?;
+            if locked.is_some() {
+                tracing::warn!(
+                    target: "audit",
+                    event = "ip_locked",
+                    ip = %ip,
+                    failures = ip_failures,
+                    lockout_secs = config.lockout_secs,
+                    "IP locked out after repeated failures"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A completed login, including its second factor, clears the username's
+    /// failures. The IP counter is kept, so one valid account does not reset a
+    /// credential-stuffing run.
+    pub async fn record_success(state: &AppState, username: &str) -> Result<(), AppError> {
+        let mut redis_conn = state.redis.clone();
+        let _: () = time_redis(
+            "DEL",
+            redis_conn.del(&[user_failures_key(username), backoff_key(username)]),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Lift a username's lockout and backoff and clear its failures
+    pub async fn unlock_user(
+        state: &AppState,
+        username: &str,
+        actor: &str,
+    ) -> Result<(), AppError> {
+        let mut redis_conn = state.redis.clone();
+        let cleared: i64 = time_redis(
+            "DEL",
+            redis_conn.del(&[
+                user_lockout_key(username),
+                backoff_key(username),
+                user_failures_key(username),
+            ]),
+        )
+        .await?;
+
+        tracing::warn!(
+            target: "audit",
+            event = "account_unlocked",
+            username = %username,
+            unlocked_by = %actor,
+            cleared,
+            "Username lockout lifted"
+        );
+        Ok(())
+    }
+
+    /// Lift an IP's lockout and clear its failures
+    pub async fn unlock_ip(state: &AppState, ip: IpAddr, actor: &str) -> Result<(), AppError> {
+        let mut redis_conn = state.redis.clone();
+        let cleared: i64 = time_redis(
+            "DEL",
+            redis_conn.del(&[ip_lockout_key(ip), ip_failures_key(ip)]),
+        )
+        .await?;
+
+        tracing::warn!(
+            target: "audit",
+            event = "ip_unlocked",
+            ip = %ip,
+            unlocked_by = %actor,
+            cleared,
+            "IP lockout lifted"
+        );
+        Ok(())
+    }
+}
+
+fn user_failures_key(username: &str) -> String {
+    format!("login_failures:user:{}", username)
+}
+
+fn ip_failures_key(ip: IpAddr) -> String {
+    format!("login_failures:ip:{}", ip)
+}
+
+fn backoff_key(username: &str) -> String {
+    format!("login_backoff:{}", username)
+}
+
+fn user_lockout_key(username: &str) -> String {
+    format!("login_lockout:user:{}", username)
+}
+
+fn ip_lockout_key(ip: IpAddr) -> String {
+    format!("login_lockout:ip:{}", ip)
+}
@@ -1,7 +1,19 @@
+pub mod account_service;
 pub mod auth_service;
+pub mod health_service;
+pub mod lockout_service;
 pub mod product_service;
+pub mod role_service;
+pub mod session_service;
+pub mod totp_service;
 pub mod user_service;
 
+pub use account_service::AccountService;
 pub use auth_service::AuthService;
+pub use health_service::HealthService;
+pub use lockout_service::LockoutService;
 pub use product_service::ProductService;
+pub use role_service::RoleService;
+pub use session_service::SessionService;
+pub use totp_service::TotpService;
 pub use user_service::UserService;
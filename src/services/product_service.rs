@@ -1,8 +1,10 @@
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::configs::pagination::PaginationConfig;
 use crate::dao::ProductDao;
-use crate::models::{CreateProductDto, Product, ProductQuery, UpdateProductDto};
+use crate::errors::AppError;
+use crate::models::{CreateProductDto, Page, PageRequest, Product, ProductQuery, UpdateProductDto};
 
 pub struct ProductService;
 
@@ -11,34 +13,76 @@ impl ProductService {
         pool: &PgPool,
         dto: CreateProductDto,
         user_id: Uuid,
-    ) -> Result<Product, String> {
-        ProductDao::create(pool, &dto, user_id)
-            .await
-            .map_err(|e| format!("Failed to create product: {}", e))
+    ) -> Result<Product, AppError> {
+        Ok(ProductDao::create(pool, &dto, user_id).await?)
     }
 
-    pub async fn get_by_id(pool: &PgPool, id: Uuid) -> Result<Product, String> {
+    pub async fn get_by_id(pool: &PgPool, id: Uuid) -> Result<Product, AppError> {
         ProductDao::find_by_id(pool, id)
             .await
-            .map_err(|_| "Product not found".to_string())
+            .map_err(|e| AppError::not_found_or(e, "Product not found"))
     }
 
-    pub async fn search(pool: &PgPool, query: ProductQuery) -> Result<Vec<Product>, String> {
-        ProductDao::find_all_dynamic(pool, &query)
-            .await
-            .map_err(|e| format!("Failed to search products: {}", e))
+    pub async fn search(
+        pool: &PgPool,
+        pagination: &PaginationConfig,
+        query: ProductQuery,
+    ) -> Result<Page<Product>, AppError> {
+        let page = PageRequest::new(
+            pagination,
+            query.limit,
+            query.cursor.as_deref(),
+            query.sort.as_deref(),
+            ProductDao::SORT_COLUMNS,
+            "-created_at",
+        )?;
+
+        let products = ProductDao::find_all_dynamic(pool, &query, &page).await?;
+        Ok(page.into_page(products))
     }
 
-    pub async fn update(pool: &PgPool, id: Uuid, dto: UpdateProductDto) -> Result<Product, String> {
-        ProductDao::update(pool, id, &dto)
-            .await
-            .map_err(|e| format!("Failed to update product: {}", e))
+    /// Only the creator of a product, or an admin, may modify it. Ownership is
+    /// part of the UPDATE itself, so it cannot change between check and write.
+    pub async fn update(
+        pool: &PgPool,
+        id: Uuid,
+        dto: UpdateProductDto,
+        user_id: Uuid,
+        is_admin: bool,
+    ) -> Result<Product, AppError> {
+        let owner = (!is_admin).then_some(user_id);
+        match ProductDao::update(pool, id, &dto, owner).await? {
+            Some(product) => Ok(product),
+            None => Err(Self::not_modifiable(pool, id, user_id).await),
+        }
     }
 
-    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<(), String> {
-        ProductDao::delete(pool, id)
-            .await
-            .map_err(|e| format!("Failed to delete product: {}", e))?;
+    /// Same ownership rule as `update`, enforced by the DELETE
+    pub async fn delete(
+        pool: &PgPool,
+        id: Uuid,
+        user_id: Uuid,
+        is_admin: bool,
+    ) -> Result<(), AppError> {
+        let owner = (!is_admin).then_some(user_id);
+        let result = ProductDao::delete(pool, id, owner).await?;
+        if result.rows_affected() == 0 {
+            return Err(Self::not_modifiable(pool, id, user_id).await);
+        }
         Ok(())
     }
+
+    /// Why a write matched no row: 404 if the product is gone, else 403
+    async fn not_modifiable(pool: &PgPool, id: Uuid, user_id: Uuid) -> AppError {
+        if let Err(e) = Self::get_by_id(pool, id).await {
+            return e;
+        }
+
+        tracing::warn!(
+            product_id = %id,
+            user_id = %user_id,
+            "Product modification denied: caller is not the owner"
+        );
+        AppError::Forbidden("You can only modify products you created".to_string())
+    }
 }
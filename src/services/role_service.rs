@@ -0,0 +1,68 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::dao::{RoleDao, UserDao};
+use crate::errors::AppError;
+use crate::models::Role;
+
+/// Role granted to every newly created user
+pub const DEFAULT_ROLE: &str = "user";
+
+pub struct RoleService;
+
+impl RoleService {
+    pub async fn get_all(pool: &PgPool) -> Result<Vec<Role>, AppError> {
+        Ok(RoleDao::find_all(pool).await?)
+    }
+
+    pub async fn get_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<Role>, AppError> {
+        UserDao::find_by_id(pool, user_id)
+            .await
+            .map_err(|e| AppError::not_found_or(e, "User not found"))?;
+
+        Ok(RoleDao::find_by_user(pool, user_id).await?)
+    }
+
+    /// Role names and permission names granted to a user, as embedded in `Claims`
+    pub async fn get_authorities(
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<(Vec<String>, Vec<String>), AppError> {
+        let roles = RoleDao::find_by_user(pool, user_id)
+            .await?
+            .into_iter()
+            .map(|role| role.name)
+            .collect();
+
+        let permissions = RoleDao::find_permissions_by_user(pool, user_id).await?;
+
+        Ok((roles, permissions))
+    }
+
+    pub async fn assign(
+        pool: &PgPool,
+        user_id: Uuid,
+        role_name: &str,
+    ) -> Result<Vec<Role>, AppError> {
+        UserDao::find_by_id(pool, user_id)
+            .await
+            .map_err(|e| AppError::not_found_or(e, "User not found"))?;
+
+        let role = RoleDao::find_by_name(pool, role_name)
+            .await
+            .map_err(|e| AppError::not_found_or(e, &format!("Role not found: {}", role_name)))?;
+
+        RoleDao::assign(pool, user_id, role.id).await?;
+
+        Self::get_by_user(pool, user_id).await
+    }
+
+    pub async fn revoke(pool: &PgPool, user_id: Uuid, role_name: &str) -> Result<(), AppError> {
+        let role = RoleDao::find_by_name(pool, role_name)
+            .await
+            .map_err(|e| AppError::not_found_or(e, &format!("Role not found: {}", role_name)))?;
+
+        RoleDao::revoke(pool, user_id, role.id).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+use crate::configs::AppState;
+use crate::errors::AppError;
+use crate::metrics::time_redis;
+use crate::models::Session;
+
+/// Check that an access token is live and record activity on its session, in
+/// one round trip. Returns 1 if the token is live.
+static TOUCH_SCRIPT: LazyLock<redis::Script> = LazyLock::new(|| {
+    redis::Script::new(
+        r"
+        if redis.call('EXISTS', KEYS[1]) == 0 then
+            return 0
+        end
+        -- Never recreate a session hash that was revoked or has expired
+        if redis.call('EXISTS', KEYS[2]) == 1 then
+            redis.call('HSET', KEYS[2], 'last_seen', ARGV[1])
+        end
+        return 1
+        ",
+    )
+});
+
+/// Active sessions per user.
+///
+/// A session is a refresh token family: it starts at login and survives every
+/// rotation until logout, revocation or refresh-token expiry. Its metadata is
+/// a hash under `session:{family_id}`, and `user_families:{user_id}` indexes
+/// the families of each user.
+pub struct SessionService;
+
+impl SessionService {
+    /// Sessions of `user_id`, most recently active first
+    pub async fn list(
+        state: &AppState,
+        user_id: Uuid,
+        current: Option<&str>,
+    ) -> Result<Vec<Session>, AppError> {
+        let user_families_key = format!("user_families:{}", user_id);
+        let mut redis_conn = state.redis.clone();
+        let families: Vec<String> =
+            time_redis("SMEMBERS", redis_conn.smembers(&user_families_key)).await?;
+        if families.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipe = redis::pipe();
+        for family_id in &families {
+            pipe.hgetall(format!("session:{}", family_id));
+        }
+        let hashes: Vec<HashMap<String, String>> =
+            time_redis("HGETALL", pipe.query_async(&mut redis_conn)).await?;
+
+        let mut sessions = Vec::new();
+        let mut expired = Vec::new();
+        for (family_id, fields) in families.into_iter().zip(hashes) {
+            match session_from_hash(&family_id, &fields, current) {
+                Some(session) => sessions.push(session),
+                None => expired.push(family_id),
+            }
+        }
+
+        // Families whose refresh token expired leave their index entry behind
+        if !expired.is_empty() {
+            let _: () = time_redis("SREM", redis_conn.srem(&user_families_key, &expired)).await?;
+        }
+
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_seen));
+        Ok(sessions)
+    }
+
+    /// Sign out one of the user's sessions
+    pub async fn revoke(state: &AppState, user_id: Uuid, session_id: &str) -> Result<(), AppError> {
+        let mut redis_conn = state.redis.clone();
+        let owned: bool = time_redis(
+            "SISMEMBER",
+            redis_conn.sismember(format!("user_families:{}", user_id), session_id),
+        )
+        .await?;
+        // Someone else's session looks the same as one that does not exist
+        if !owned {
+            return Err(AppError::NotFound("Session not found".to_string()));
+        }
+
+        Self::revoke_family(state, session_id).await?;
+        tracing::info!(user_id = %user_id, session_id = %session_id, "Session revoked");
+        Ok(())
+    }
+
+    /// Sign the user out everywhere, optionally keeping one session. Returns
+    /// how many sessions were revoked.
+    pub async fn revoke_all(
+        state: &AppState,
+        user_id: Uuid,
+        except: Option<&str>,
+    ) -> Result<usize, AppError> {
+        let user_families_key = format!("user_families:{}", user_id);
+        let mut redis_conn = state.redis.clone();
+        let families: Vec<String> =
+            time_redis("SMEMBERS", redis_conn.smembers(&user_families_key)).await?;
+
+        let revoked: Vec<&String> = families
+            .iter()
+            .filter(|f| Some(f.as_str()) != except)
+            .collect();
+        for family_id in &revoked {
+            Self::revoke_family(state, family_id).await?;
+        }
+        // Also drops index entries of families that have no session hash
+        if !revoked.is_empty() {
+            let _: () = time_redis("SREM", redis_conn.srem(&user_families_key, &revoked)).await?;
+        }
+
+        tracing::info!(user_id = %user_id, sessions = revoked.len(), "Revoked all sessions");
+        Ok(revoked.len())
+    }
+
+    /// Whether `token` is live; records the request as session activity
+    pub async fn touch(state: &AppState, token: &str, family_id: &str) -> Result<bool, AppError> {
+        let mut redis_conn = state.redis.clone();
+        let live: i64 = time_redis(
+            "EVALSHA",
+            TOUCH_SCRIPT
+                .key(format!("token:{}", token))
+                .key(format!("session:{}", family_id))
+                .arg(Utc::now().timestamp())
+                .invoke_async(&mut redis_conn),
+        )
+        .await?;
+        Ok(live == 1)
+    }
+
+    /// Delete every access and refresh token issued in a token family, and the
+    /// session built on it
+    pub async fn revoke_family(state: &AppState, family_id: &str) -> Result<(), AppError> {
+        let family_key = format!("refresh_family:{}", family_id);
+        let session_key = format!("session:{}", family_id);
+
+        let mut redis_conn = state.redis.clone();
+        let keys: Vec<String> = time_redis("SMEMBERS", redis_conn.smembers(&family_key)).await?;
+        let user_id: Option<String> =
+            time_redis("HGET", redis_conn.hget(&session_key, "user_id")).await?;
+
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for key in &keys {
+            pipe.del(key).ignore();
+        }
+        pipe.del(&family_key).ignore().del(&session_key).ignore();
+        if let Some(user_id) = user_id {
+            pipe.srem(format!("user_families:{}", user_id), family_id)
+                .ignore();
+        }
+
+        time_redis("MULTI", pipe.query_async::<()>(&mut redis_conn)).await?;
+
+        Ok(())
+    }
+}
+
+/// `None` when the hash is gone, i.e. the session expired
+fn session_from_hash(
+    family_id: &str,
+    fields: &HashMap<String, String>,
+    current: Option<&str>,
+) -> Option<Session> {
+    let timestamp = |name: &str| {
+        fields
+            .get(name)
+            .and_then(|value| value.parse::<i64>().ok())
+            .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+    };
+
+    let created_at = timestamp("created_at")?;
+    Some(Session {
+        id: family_id.to_string(),
+        created_at,
+        last_seen: timestamp("last_seen").unwrap_or(created_at),
+        ip: fields.get("ip").cloned(),
+        user_agent: fields.get("user_agent").cloned(),
+        current: current == Some(family_id),
+    })
+}
@@ -0,0 +1,261 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use uuid::Uuid;
+
+use crate::configs::AppState;
+use crate::dao::TotpDao;
+use crate::errors::AppError;
+use crate::models::{RecoveryCodes, TotpEnrollment, UserTotp};
+use crate::services::LockoutService;
+
+/// RFC 6238 defaults, which every authenticator app supports
+const STEP_SECS: i64 = 30;
+const DIGITS: u32 = 6;
+/// Steps either side of now that are still accepted, for clock drift
+const SKEW_STEPS: i64 = 1;
+/// 160 bits, the HMAC-SHA1 block-size recommendation from RFC 4226
+const SECRET_LEN: usize = 20;
+const RECOVERY_CODE_COUNT: usize = 10;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub struct TotpService;
+
+impl TotpService {
+    /// Generate a fresh secret. It is not used for login until confirmed with a code.
+    pub async fn enroll(
+        state: &AppState,
+        user_id: Uuid,
+        username: &str,
+    ) -> Result<TotpEnrollment, AppError> {
+        let secret = random_bytes(SECRET_LEN)?;
+
+        let result = TotpDao::upsert_pending(&state.db, user_id, &secret).await?;
+        if result.rows_affected() == 0 {
+            return Err(already_enabled());
+        }
+
+        let secret = base32(&secret);
+        let issuer = &state.auth.totp_issuer;
+        let provisioning_uri = format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+            percent_encode(issuer),
+            percent_encode(username),
+            secret,
+            percent_encode(issuer),
+            DIGITS,
+            STEP_SECS
+        );
+
+        tracing::info!(user_id = %user_id, "TOTP enrollment started");
+        Ok(TotpEnrollment {
+            secret,
+            provisioning_uri,
+        })
+    }
+
+    /// Enable 2FA once the user proves their authenticator works, and hand out
+    /// recovery codes. Only the hashes are kept.
+    pub async fn confirm(
+        state: &AppState,
+        user_id: Uuid,
+        code: &str,
+    ) -> Result<RecoveryCodes, AppError> {
+        let totp = TotpDao::find(&state.db, user_id)
+            .await?
+            .ok_or_else(|| AppError::BadRequest("No pending TOTP enrollment".to_string()))?;
+        if totp.is_enabled() {
+            return Err(already_enabled());
+        }
+
+        let step = matching_step(&totp.secret, code, Utc::now().timestamp())
+            .ok_or_else(|| AppError::BadRequest("Invalid verification code".to_string()))?;
+
+        let codes = (0..RECOVERY_CODE_COUNT)
+            .map(|_| recovery_code())
+            .collect::<Result<Vec<_>, _>>()?;
+        let hashes: Vec<String> = codes.iter().map(|c| hash_recovery_code(c)).collect();
+
+        let result = TotpDao::enable(&state.db, user_id, step, &hashes).await?;
+        if result.rows_affected() == 0 {
+            // Confirmed concurrently by another request
+            return Err(already_enabled());
+        }
+
+        tracing::info!(user_id = %user_id, "Two-factor authentication enabled");
+        Ok(RecoveryCodes {
+            recovery_codes: codes,
+        })
+    }
+
+    /// Turn 2FA off; requires a current code or a recovery code. Wrong codes
+    /// count toward the same backoff and lockout as failed logins, so a stolen
+    /// access token is not enough to guess the code.
+    pub async fn disable(
+        state: &AppState,
+        user_id: Uuid,
+        username: &str,
+        ip: Option<IpAddr>,
+        code: &str,
+    ) -> Result<(), AppError> {
+        let totp = TotpDao::find(&state.db, user_id)
+            .await?
+            .filter(UserTotp::is_enabled)
+            .ok_or_else(|| {
+                AppError::BadRequest("Two-factor authentication is not enabled".to_string())
+            })?;
+
+        if let Some(retry_after) = LockoutService::retry_after(state, username, ip).await? {
+            return Err(AppError::TooManyRequests(
+                "Too many failed attempts. Please try again later.".to_string(),
+                Some(retry_after),
+            ));
+        }
+
+        if !Self::verify(state, &totp, code).await? {
+            LockoutService::record_failure(state, username, ip).await?;
+            return Err(AppError::Unauthorized(
+                "Invalid verification code".to_string(),
+            ));
+        }
+
+        TotpDao::delete(&state.db, user_id).await?;
+
+        tracing::info!(user_id = %user_id, "Two-factor authentication disabled");
+        Ok(())
+    }
+
+    pub async fn is_enabled(state: &AppState, user_id: Uuid) -> Result<bool, AppError> {
+        Ok(TotpDao::is_enabled(&state.db, user_id).await?)
+    }
+
+    /// Check a second factor for `user_id`, consuming it: a TOTP code cannot be
+    /// replayed and a recovery code works once
+    pub async fn verify_second_factor(
+        state: &AppState,
+        user_id: Uuid,
+        code: &str,
+    ) -> Result<bool, AppError> {
+        match TotpDao::find(&state.db, user_id).await? {
+            Some(totp) if totp.is_enabled() => Self::verify(state, &totp, code).await,
+            _ => Ok(false),
+        }
+    }
+
+    async fn verify(state: &AppState, totp: &UserTotp, code: &str) -> Result<bool, AppError> {
+        let code = code.trim();
+
+        if code.len() == DIGITS as usize && code.bytes().all(|b| b.is_ascii_digit()) {
+            let Some(step) = matching_step(&totp.secret, code, Utc::now().timestamp()) else {
+                return Ok(false);
+            };
+            let result = TotpDao::record_step(&state.db, totp.user_id, step).await?;
+            if result.rows_affected() == 0 {
+                tracing::warn!(user_id = %totp.user_id, "Replayed TOTP code rejected");
+                return Ok(false);
+            }
+            return Ok(true);
+        }
+
+        let result =
+            TotpDao::use_recovery_code(&state.db, totp.user_id, &hash_recovery_code(code)).await?;
+        if result.rows_affected() == 1 {
+            tracing::warn!(user_id = %totp.user_id, "Recovery code used");
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+fn already_enabled() -> AppError {
+    AppError::Conflict("Two-factor authentication is already enabled".to_string())
+}
+
+/// Time step whose code matches `code`, searching `SKEW_STEPS` either side of `now`
+fn matching_step(secret: &[u8], code: &str, now: i64) -> Option<i64> {
+    let code: u32 = code.trim().parse().ok()?;
+    let current = now / STEP_SECS;
+
+    (current - SKEW_STEPS..=current + SKEW_STEPS).find(|&step| hotp(secret, step as u64) == code)
+}
+
+/// RFC 4226 HOTP with dynamic truncation
+fn hotp(secret: &[u8], counter: u64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    binary % 10u32.pow(DIGITS)
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, AppError> {
+    let mut bytes = vec![0u8; len];
+    getrandom::fill(&mut bytes)
+        .map_err(|e| AppError::Internal(format!("Failed to generate random bytes: {}", e)))?;
+    Ok(bytes)
+}
+
+/// Ten base32 characters (50 bits), shown as `xxxxx-xxxxx`
+fn recovery_code() -> Result<String, AppError> {
+    let code = base32(&random_bytes(7)?).to_ascii_lowercase();
+    Ok(format!("{}-{}", &code[..5], &code[5..10]))
+}
+
+/// Recovery codes are random, so a fast hash is enough. Dashes, spaces and case
+/// are ignored when comparing.
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    Sha256::digest(normalized.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// RFC 4648 base32 without padding, as authenticator apps expect
+fn base32(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    out
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
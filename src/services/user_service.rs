@@ -2,49 +2,82 @@ use bcrypt::{DEFAULT_COST, hash};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::dao::UserDao;
-use crate::models::{CreateUserDto, UpdateUserDto, User};
+use crate::configs::AppState;
+use crate::configs::pagination::PaginationConfig;
+use crate::dao::{RoleDao, UserDao};
+use crate::errors::AppError;
+use crate::models::{CreateUserDto, Page, PageRequest, UpdateUserDto, User, UserQuery};
+use crate::services::SessionService;
+use crate::services::role_service::DEFAULT_ROLE;
 
 pub struct UserService;
 
 impl UserService {
-    pub async fn create(pool: &PgPool, dto: CreateUserDto) -> Result<User, String> {
-        let password_hash =
-            hash(&dto.password, DEFAULT_COST).map_err(|_| "Failed to hash password")?;
+    pub async fn create(pool: &PgPool, dto: CreateUserDto) -> Result<User, AppError> {
+        let password_hash = hash(&dto.password, DEFAULT_COST)
+            .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))?;
 
-        UserDao::create(pool, &dto, &password_hash)
+        let role = RoleDao::find_by_name(pool, DEFAULT_ROLE)
             .await
-            .map_err(|e| format!("Failed to create user: {}", e))
+            .map_err(|e| AppError::Internal(format!("Failed to find default role: {}", e)))?;
+        let user = UserDao::create(pool, &dto, &password_hash, role.id).await?;
+
+        Ok(user)
     }
 
-    pub async fn get_by_id(pool: &PgPool, id: Uuid) -> Result<User, String> {
+    pub async fn get_by_id(pool: &PgPool, id: Uuid) -> Result<User, AppError> {
         UserDao::find_by_id(pool, id)
             .await
-            .map_err(|_| "User not found".to_string())
+            .map_err(|e| AppError::not_found_or(e, "User not found"))
     }
 
-    pub async fn get_all(pool: &PgPool) -> Result<Vec<User>, String> {
-        UserDao::find_all(pool)
-            .await
-            .map_err(|e| format!("Failed to fetch users: {}", e))
+    pub async fn get_all(
+        pool: &PgPool,
+        pagination: &PaginationConfig,
+        query: UserQuery,
+    ) -> Result<Page<User>, AppError> {
+        let page = PageRequest::new(
+            pagination,
+            query.limit,
+            query.cursor.as_deref(),
+            query.sort.as_deref(),
+            UserDao::SORT_COLUMNS,
+            "-created_at",
+        )?;
+
+        let users = UserDao::find_all(pool, &page).await?;
+        Ok(page.into_page(users))
     }
 
-    pub async fn update(pool: &PgPool, id: Uuid, dto: UpdateUserDto) -> Result<User, String> {
+    /// Update a user; a new password signs them out everywhere
+    pub async fn update(state: &AppState, id: Uuid, dto: UpdateUserDto) -> Result<User, AppError> {
         let password_hash = if let Some(password) = &dto.password {
-            Some(hash(password, DEFAULT_COST).map_err(|_| "Failed to hash password")?)
+            Some(
+                hash(password, DEFAULT_COST)
+                    .map_err(|e| AppError::Internal(format!("Failed to hash password: {}", e)))?,
+            )
         } else {
             None
         };
 
-        UserDao::update(pool, id, &dto, password_hash.as_deref())
+        let user = UserDao::update(&state.db, id, &dto, password_hash.as_deref())
             .await
-            .map_err(|e| format!("Failed to update user: {}", e))
+            .map_err(|e| AppError::not_found_or(e, "User not found"))?;
+
+        if password_hash.is_some() {
+            SessionService::revoke_all(state, id, None).await?;
+        }
+        Ok(user)
     }
 
-    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<(), String> {
-        UserDao::delete(pool, id)
-            .await
-            .map_err(|e| format!("Failed to delete user: {}", e))?;
+    /// Delete a user and revoke all of their sessions
+    pub async fn delete(state: &AppState, id: Uuid) -> Result<(), AppError> {
+        let result = UserDao::delete(&state.db, id).await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("User not found".to_string()));
+        }
+
+        SessionService::revoke_all(state, id, None).await?;
         Ok(())
     }
 }
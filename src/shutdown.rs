@@ -0,0 +1,92 @@
+use std::future::Future;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+
+/// Cancellation signal and registry for background tasks.
+///
+/// Tasks started with [`Shutdown::spawn`] are cancelled once HTTP draining has
+/// finished, and shutdown waits for them before the connection pools close.
+///
+/// Tasks run on the runtime that created the `Shutdown`, which must be the one
+/// `main` runs on. Actix workers each have their own runtime and drop it when
+/// they stop, so a task spawned there would be gone before it could be awaited.
+#[derive(Clone)]
+pub struct Shutdown {
+    runtime: Handle,
+    token: CancellationToken,
+    tasks: TaskTracker,
+}
+
+impl Shutdown {
+    /// Panics outside a Tokio runtime
+    pub fn new() -> Self {
+        Self {
+            runtime: Handle::current(),
+            token: CancellationToken::new(),
+            tasks: TaskTracker::new(),
+        }
+    }
+
+    /// Run `task` in the background until it finishes or shutdown cancels it
+    pub fn spawn<F>(&self, name: &'static str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let token = self.token.clone();
+        let task = async move {
+            tokio::select! {
+                _ = task => {}
+                _ = token.cancelled() => {
+                    tracing::debug!(task = name, "Background task cancelled");
+                }
+            }
+        };
+        self.tasks.spawn_on(task, &self.runtime);
+    }
+
+    /// Cancel every background task and wait up to `timeout` for them to exit.
+    /// Returns false if some were still running when the timeout elapsed.
+    pub async fn cancel(&self, timeout: Duration) -> bool {
+        self.token.cancel();
+        self.tasks.close();
+        tokio::time::timeout(timeout, self.tasks.wait())
+            .await
+            .is_ok()
+    }
+
+    pub fn task_count(&self) -> usize {
+        self.tasks.len()
+    }
+}
+
+/// Resolves on SIGTERM or Ctrl-C
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("Received Ctrl-C"),
+        _ = terminate => tracing::info!("Received SIGTERM"),
+    }
+}
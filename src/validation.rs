@@ -0,0 +1,126 @@
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpRequest, web};
+use futures::future::LocalBoxFuture;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::ops::Deref;
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::errors::AppError;
+
+/// A single invalid field in a 422 response
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
+    pub message: String,
+}
+
+/// JSON body extractor that runs the DTO's `Validate` rules before the handler.
+///
+/// Deserialization errors are reported as 400, rule violations as 422.
+pub struct ValidatedJson<T>(pub T);
+
+impl<T> ValidatedJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ValidatedJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = web::Json::<T>::from_request(req, payload);
+
+        Box::pin(async move {
+            let value = json.await?.into_inner();
+            value.validate().map_err(AppError::from)?;
+            Ok(ValidatedJson(value))
+        })
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut fields: Vec<FieldError> = errors
+            .field_errors()
+            .into_iter()
+            .flat_map(|(field, errors)| {
+                errors.iter().map(move |error| FieldError {
+                    field: field.to_string(),
+                    code: error.code.to_string(),
+                    message: error
+                        .message
+                        .as_ref()
+                        .map(|m| m.to_string())
+                        .unwrap_or_else(|| default_message(error)),
+                })
+            })
+            .collect();
+        fields.sort_by(|a, b| a.field.cmp(&b.field).then(a.code.cmp(&b.code)));
+
+        AppError::Validation(fields)
+    }
+}
+
+/// Human-readable fallback for rules declared without a `message`
+fn default_message(error: &ValidationError) -> String {
+    let param = |name: &str| error.params.get(name).map(|v| v.to_string());
+
+    match error.code.as_ref() {
+        "email" => "Must be a valid email address".to_string(),
+        "length" => match (param("min"), param("max")) {
+            (Some(min), Some(max)) => format!("Length must be between {} and {}", min, max),
+            (Some(min), None) => format!("Length must be at least {}", min),
+            (None, Some(max)) => format!("Length must be at most {}", max),
+            _ => "Invalid length".to_string(),
+        },
+        "range" => match (param("min"), param("max")) {
+            (Some(min), Some(max)) => format!("Must be between {} and {}", min, max),
+            (Some(min), None) => format!("Must be at least {}", min),
+            (None, Some(max)) => format!("Must be at most {}", max),
+            _ => "Out of range".to_string(),
+        },
+        code => format!("Failed validation: {}", code),
+    }
+}
+
+/// Require at least one letter and one digit; length is checked separately
+pub fn validate_password_strength(password: &str) -> Result<(), ValidationError> {
+    let has_letter = password.chars().any(|c| c.is_alphabetic());
+    let has_digit = password.chars().any(|c| c.is_ascii_digit());
+
+    if has_letter && has_digit {
+        Ok(())
+    } else {
+        Err(ValidationError::new("password_strength")
+            .with_message("Password must contain at least one letter and one digit".into()))
+    }
+}
+
+/// Usernames are limited to ASCII letters, digits, `_`, `.` and `-`
+pub fn validate_username(username: &str) -> Result<(), ValidationError> {
+    if username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("username_charset")
+            .with_message("Username may only contain letters, digits, '_', '.' and '-'".into()))
+    }
+}